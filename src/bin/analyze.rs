@@ -0,0 +1,61 @@
+//! Analyses one turn of a recorded game: the position with the shadows of
+//! the next day, the heuristic score of every legal action and the bot's
+//! decision after a long search.
+//!
+//! Usage: cargo run --release --bin analyze -- --input game-1.txt --turn 12 --think-ms 1000 --debug
+//!
+//! Turns are numbered from 0 in the transcript, the last one by default.
+//! The bot starts fresh on the turn and thinks `--think-ms`, or
+//! `--iterations`. `--ascii` drops the colors and `--svg file` also draws
+//! the position and the decision to an SVG file.
+
+use std::fs;
+use std::time::Duration;
+
+use rustbot::bot::bot_from_settings;
+use rustbot::config::{flag, parsed_setting, setting};
+use rustbot::heuristics::calculate_game_points_from_action;
+use rustbot::render::{render, RenderOptions};
+use rustbot::rng::seed_from_settings;
+use rustbot::svg::render_svg;
+use rustbot::time::Deadline;
+use rustbot::transcript::Transcript;
+use rustbot::weights::EvalWeights;
+
+fn main() {
+    let input = setting("input").unwrap_or_else(|| panic!("Missing the transcript, --input"));
+    let transcript = Transcript::open(&input);
+    if transcript.turns.is_empty() {
+        panic!("No turn could be read from {}", input);
+    }
+    let number = parsed_setting("turn").unwrap_or(transcript.turns.len() - 1);
+    let turn = transcript.turns.get(number).unwrap_or_else(|| panic!("No turn {}, the transcript has {}", number, transcript.turns.len()));
+    let board = &transcript.board;
+
+    print!("{}", render(&turn.state, board, &RenderOptions { colors: !flag("ascii"), shadows: true }));
+
+    let weights = EvalWeights::from_settings();
+    let mut scored: Vec<_> = turn
+        .actions
+        .iter()
+        .map(|action| (calculate_game_points_from_action(action, turn.state.nutrients, board, &turn.state.forest, turn.state.day, 0, &weights), action))
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(b.1)));
+    println!("Heuristic scores:");
+    for (points, action) in scored {
+        println!("{:>5}  {}", points, action);
+    }
+
+    let seed = seed_from_settings();
+    eprintln!("Seed: {}", seed);
+    let mut bot = bot_from_settings(seed);
+    bot.init(board);
+
+    let deadline = Deadline::after(Duration::from_millis(parsed_setting("think-ms").unwrap_or(1000))).with_max_iterations(parsed_setting("iterations"));
+    let decision = bot.decide(&turn.state, &turn.actions, deadline);
+    println!("Decision: {} {}", decision.action, decision.message);
+
+    if let Some(path) = setting("svg") {
+        fs::write(&path, render_svg(&turn.state, board, Some(&decision), true)).unwrap_or_else(|e| panic!("Failed to write {}: {}", path, e));
+    }
+}
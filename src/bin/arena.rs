@@ -0,0 +1,483 @@
+//! Plays a game between two bot executables the way CodinGame does: each
+//! bot is spawned once and sent the exact referee input, the area first and
+//! then every turn the context, the forest and its legal actions, while the
+//! game is refereed with the simulation of the rules.
+//!
+//! Usage: cargo run --release --bin arena -- --p1 target/release/rustbot --p2 "target/release/rustbot --bot greedy"
+//!
+//! Commands are run by the shell. As on CodinGame, a bot that does not
+//! answer within the limits (1000 ms on its first turn, 100 ms afterwards,
+//! see `--first-turn-ms` and `--turn-ms`), crashes or prints an action that
+//! is not in its list loses the game. The map comes from `--seed`, `--log`
+//! writes the input sent to each bot to `<log>-1.txt` and `<log>-2.txt` so
+//! that any turn can be replayed, and `--verbose` shows the actions and the
+//! bots' stderr.
+//!
+//! With `--games N` the bots play a tournament: pairs of games on random
+//! maps, the seats swapped in the second game of each pair, over
+//! `--concurrency` games at a time (1 by default, as the bots compete for
+//! time). The standings of the first bot are reported after every game:
+//! wins, draws and losses, the average score margin and the Elo difference
+//! with its 95% confidence interval. With `--sprt-elo1 E` the tournament
+//! stops as soon as a sequential probability ratio test concludes between
+//! an Elo difference of `--sprt-elo0` (0 by default) and E, with false
+//! positive and negative rates `--alpha` and `--beta` (0.05 by default).
+//!
+//! Usage: cargo run --release --bin arena -- --p1 "target/release/rustbot" --p2 "./baseline" --games 200 --sprt-elo1 10
+
+use std::fmt;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::mem;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rustbot::config::{flag, parsed_setting, setting};
+use rustbot::game::{Action, Area};
+use rustbot::mapgen::{generate_board, initial_state};
+use rustbot::protocol::{area_input, turn_input};
+use rustbot::rng::{seed_from_settings, Xorshift};
+use rustbot::rules::{apply_joint_actions, legal_actions, State};
+use rustbot::stats::confidence_radius;
+
+enum Failure {
+    Timeout(Duration),
+    Crash,
+    Invalid(String),
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::Timeout(limit) => write!(f, "did not answer within {} ms", limit.as_millis()),
+            Failure::Crash => write!(f, "stopped"),
+            Failure::Invalid(output) => write!(f, "played an invalid action: {}", output),
+        }
+    }
+}
+
+struct BotProcess {
+    child: Child,
+    stdin: ChildStdin,
+    // lines of the bot's stdout, read by a separate thread so that waiting
+    // for them can time out
+    lines: Receiver<String>,
+    transcript: String,
+}
+
+impl BotProcess {
+    fn spawn(command: &str, verbose: bool) -> BotProcess {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(if verbose { Stdio::inherit() } else { Stdio::null() })
+            .spawn()
+            .unwrap_or_else(|e| panic!("Failed to start {}: {}", command, e));
+
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+        let (sender, lines) = channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        BotProcess { child, stdin, lines, transcript: String::new() }
+    }
+
+    fn send(&mut self, input: &str) -> Result<(), Failure> {
+        self.transcript.push_str(input);
+        self.stdin.write_all(input.as_bytes()).and_then(|_| self.stdin.flush()).map_err(|_| Failure::Crash)
+    }
+
+    // The answer to the input sent at `sent`
+    fn answer(&self, sent: Instant, limit: Duration) -> Result<String, Failure> {
+        let timeout = (sent + limit).saturating_duration_since(Instant::now());
+        self.lines.recv_timeout(timeout).map_err(|e| match e {
+            RecvTimeoutError::Timeout => Failure::Timeout(limit),
+            RecvTimeoutError::Disconnected => Failure::Crash,
+        })
+    }
+}
+
+impl Drop for BotProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+struct Limits {
+    first_turn: Duration,
+    turn: Duration,
+}
+
+struct GameResult {
+    state: State,
+    // player that lost by breaking the rules, and how
+    failure: Option<(usize, Failure)>,
+    transcripts: [String; 2],
+}
+
+impl GameResult {
+    fn winner(&self) -> Option<usize> {
+        match &self.failure {
+            Some((player, _)) => Some(1 - player),
+            None => self.state.winner(),
+        }
+    }
+}
+
+// One answer of `player`, checked against the legal actions it was sent
+fn read_action(bot: &BotProcess, sent: Instant, limit: Duration, legal: &[Action]) -> Result<Action, Failure> {
+    let output = bot.answer(sent, limit)?;
+    match output.parse::<Action>() {
+        Ok(action) if legal.contains(&action) => Ok(action),
+        _ => Err(Failure::Invalid(output)),
+    }
+}
+
+fn play_game(commands: &[String; 2], board: &Area, mut state: State, limits: &Limits, verbose: bool) -> GameResult {
+    let mut bots = [BotProcess::spawn(&commands[0], verbose), BotProcess::spawn(&commands[1], verbose)];
+    let mut first_turn = [true; 2];
+    let mut failure = None;
+
+    'game: while !state.is_game_over() {
+        // Both bots get their input before either answer is awaited, as they
+        // play simultaneously
+        let mut sent = [None; 2];
+        let mut legal = [vec![], vec![]];
+        for (player, bot) in bots.iter_mut().enumerate() {
+            if state.waiting[player] {
+                continue;
+            }
+            let view = if player == 0 { state.clone() } else { state.swapped() };
+            legal[player] = legal_actions(&view, board, 0);
+
+            let mut input = if first_turn[player] { area_input(board) } else { String::new() };
+            input.push_str(&turn_input(&view, &legal[player]));
+            if let Err(e) = bot.send(&input) {
+                failure = Some((player, e));
+                break 'game;
+            }
+            sent[player] = Some(Instant::now());
+        }
+
+        let mut actions = [Action::Wait; 2];
+        for (player, bot) in bots.iter().enumerate() {
+            let Some(sent) = sent[player] else { continue };
+            let limit = if first_turn[player] { limits.first_turn } else { limits.turn };
+            first_turn[player] = false;
+            match read_action(bot, sent, limit, &legal[player]) {
+                Ok(action) => actions[player] = action,
+                Err(e) => {
+                    failure = Some((player, e));
+                    break 'game;
+                }
+            }
+        }
+
+        if verbose {
+            let played = |player: usize| if sent[player].is_some() { actions[player].to_string() } else { "-".to_string() };
+            eprintln!("Day {} sun {:?} score {:?}: {} | {}", state.day, state.sun, state.score, played(0), played(1));
+        }
+        apply_joint_actions(&mut state, board, actions);
+    }
+
+    let transcripts = [mem::take(&mut bots[0].transcript), mem::take(&mut bots[1].transcript)];
+    GameResult { state, failure, transcripts }
+}
+
+// Results of the first bot against the second over a tournament
+#[derive(Copy, Clone, Default)]
+struct Standings {
+    wins: usize,
+    draws: usize,
+    losses: usize,
+    // sum of the first bot's score minus the second's
+    margin: i64,
+}
+
+impl Standings {
+    fn add(&mut self, result: &GameResult, first_seat: usize) {
+        match result.winner() {
+            Some(winner) if winner == first_seat => self.wins += 1,
+            Some(_) => self.losses += 1,
+            None => self.draws += 1,
+        }
+        let score = result.state.score;
+        self.margin += (score[first_seat] - score[1 - first_seat]) as i64;
+    }
+
+    fn games(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+
+    // Mean points per game, a draw counting half
+    fn score(&self) -> f64 {
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games() as f64
+    }
+
+    fn variance(&self) -> f64 {
+        let score = self.score();
+        let games = self.games() as f64;
+        (self.wins as f64 * (1.0 - score).powi(2) + self.draws as f64 * (0.5 - score).powi(2) + self.losses as f64 * score.powi(2)) / games
+    }
+
+    // Elo difference and the half width of its 95% confidence interval
+    fn elo(&self) -> (f64, f64) {
+        let radius = confidence_radius(self.variance(), self.games() as f64);
+        let score = self.score();
+        let (low, high) = (elo(score - radius), elo(score + radius));
+        (elo(score), (high - low) / 2.0)
+    }
+
+    // Log likelihood ratio of the Elo difference being elo1 rather than
+    // elo0, in the normal approximation of the score. A win and a loss are
+    // added so that the variance is not zero after a one-sided start.
+    fn llr(&self, elo0: f64, elo1: f64) -> f64 {
+        let prior = Standings { wins: self.wins + 1, losses: self.losses + 1, ..*self };
+        let (score0, score1) = (expected_score(elo0), expected_score(elo1));
+        prior.games() as f64 * (score1 - score0) * (2.0 * prior.score() - score0 - score1) / (2.0 * prior.variance())
+    }
+}
+
+impl fmt::Display for Standings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (elo, error) = self.elo();
+        write!(
+            f,
+            "W-D-L {}-{}-{}, margin {:+.1}, Elo {:+.1} +/- {:.1}",
+            self.wins,
+            self.draws,
+            self.losses,
+            self.margin as f64 / self.games() as f64,
+            elo,
+            error
+        )
+    }
+}
+
+// Elo differences are capped where the score gets to 0 or 1
+const MAX_ELO: f64 = 1000.0;
+
+fn expected_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+fn elo(score: f64) -> f64 {
+    if score <= 0.0 || score >= 1.0 {
+        return MAX_ELO.copysign(score - 0.5);
+    }
+    (-400.0 * (1.0 / score - 1.0).log10()).clamp(-MAX_ELO, MAX_ELO)
+}
+
+// Sequential probability ratio test of H0: Elo = elo0 against H1: Elo = elo1
+struct Sprt {
+    elo0: f64,
+    elo1: f64,
+    // bounds of the log likelihood ratio for the false positive and negative rates
+    lower: f64,
+    upper: f64,
+}
+
+impl Sprt {
+    fn new(elo0: f64, elo1: f64, alpha: f64, beta: f64) -> Sprt {
+        Sprt { elo0, elo1, lower: (beta / (1.0 - alpha)).ln(), upper: ((1.0 - beta) / alpha).ln() }
+    }
+
+    // Some(true) once H1 is accepted, Some(false) once H0 is
+    fn decision(&self, standings: &Standings) -> Option<bool> {
+        let llr = standings.llr(self.elo0, self.elo1);
+        if llr >= self.upper {
+            Some(true)
+        } else if llr <= self.lower {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+struct Tournament {
+    commands: [String; 2],
+    limits: Limits,
+    games: usize,
+    seed: u64,
+    sprt: Option<Sprt>,
+    log: Option<String>,
+    verbose: bool,
+}
+
+impl Tournament {
+    // Game `game` of the tournament: games go in pairs on the same map, the
+    // second of a pair with the seats swapped. Returns the result and the
+    // seat of the first bot.
+    fn play(&self, game: usize) -> (GameResult, usize) {
+        let pair = (game / 2) as u64;
+        let first_seat = game % 2;
+
+        let mut rng = Xorshift::new(self.seed.wrapping_add(pair));
+        let board = generate_board(&mut rng);
+        let state = initial_state(&board, &mut rng);
+
+        let commands = if first_seat == 0 { self.commands.clone() } else { [self.commands[1].clone(), self.commands[0].clone()] };
+        let result = play_game(&commands, &board, state, &self.limits, self.verbose);
+
+        if let Some(log) = &self.log {
+            let prefix = if self.games == 1 { log.clone() } else { format!("{}-{}", log, game + 1) };
+            for (player, transcript) in result.transcripts.iter().enumerate() {
+                let path = format!("{}-{}.txt", prefix, player + 1);
+                fs::write(&path, transcript).unwrap_or_else(|e| panic!("Failed to write {}: {}", path, e));
+            }
+        }
+        (result, first_seat)
+    }
+
+    // Plays the games over `concurrency` threads, until all are played or
+    // the SPRT concludes
+    fn run(&self, concurrency: usize) -> Standings {
+        let next_game = AtomicUsize::new(0);
+        let stop = AtomicBool::new(false);
+        let standings = Mutex::new(Standings::default());
+
+        thread::scope(|scope| {
+            for _ in 0..concurrency {
+                scope.spawn(|| loop {
+                    let game = next_game.fetch_add(1, Ordering::Relaxed);
+                    if game >= self.games || stop.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let (result, first_seat) = self.play(game);
+
+                    let mut standings = standings.lock().unwrap();
+                    standings.add(&result, first_seat);
+                    report_game(game, &result, first_seat);
+                    if self.games > 1 {
+                        println!("  {}", standings);
+                    }
+                    if let Some(sprt) = &self.sprt {
+                        let llr = standings.llr(sprt.elo0, sprt.elo1);
+                        println!("  LLR {:.2} ({:.2}, {:.2})", llr, sprt.lower, sprt.upper);
+                        if let Some(accepted) = sprt.decision(&standings) {
+                            println!("SPRT: {}", if accepted { "H1 accepted, bot 1 is better" } else { "H0 accepted, bot 1 is not better" });
+                            stop.store(true, Ordering::Relaxed);
+                        }
+                    }
+                });
+            }
+        });
+
+        standings.into_inner().unwrap()
+    }
+}
+
+// The outcome of one game, players named by the order of their commands
+fn report_game(game: usize, result: &GameResult, first_seat: usize) {
+    let bot = |seat: usize| if seat == first_seat { 1 } else { 2 };
+    if let Some((seat, failure)) = &result.failure {
+        println!("Game {}: bot {} {} on day {}", game + 1, bot(*seat), failure, result.state.day);
+    }
+    let score = result.state.score;
+    let (first, second) = (score[first_seat], score[1 - first_seat]);
+    match result.winner() {
+        Some(winner) => println!("Game {}: bot {} wins {} - {}", game + 1, bot(winner), first, second),
+        None => println!("Game {}: draw {} - {}", game + 1, first, second),
+    }
+}
+
+fn main() {
+    let commands = [
+        setting("p1").unwrap_or_else(|| panic!("Missing the first bot's command, --p1")),
+        setting("p2").unwrap_or_else(|| panic!("Missing the second bot's command, --p2")),
+    ];
+    let limits = Limits {
+        first_turn: Duration::from_millis(parsed_setting("first-turn-ms").unwrap_or(1000)),
+        turn: Duration::from_millis(parsed_setting("turn-ms").unwrap_or(100)),
+    };
+
+    // H0 and H1 of the SPRT as Elo differences of the first bot
+    let sprt = parsed_setting::<f64>("sprt-elo1").map(|elo1| {
+        Sprt::new(parsed_setting("sprt-elo0").unwrap_or(0.0), elo1, parsed_setting("alpha").unwrap_or(0.05), parsed_setting("beta").unwrap_or(0.05))
+    });
+
+    let seed = seed_from_settings();
+    eprintln!("Seed: {}", seed);
+
+    let tournament = Tournament {
+        commands,
+        limits,
+        games: parsed_setting("games").unwrap_or(1),
+        seed,
+        sprt,
+        log: setting("log"),
+        verbose: flag("verbose"),
+    };
+    let standings = tournament.run(parsed_setting("concurrency").unwrap_or(1));
+
+    if tournament.games > 1 {
+        println!("Bot 1 against bot 2 after {} games: {}", standings.games(), standings);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn standings(wins: usize, draws: usize, losses: usize) -> Standings {
+        Standings { wins, draws, losses, margin: 0 }
+    }
+
+    #[test]
+    fn elo_is_the_inverse_of_the_expected_score() {
+        assert_eq!(expected_score(0.0), 0.5);
+        assert!((expected_score(400.0) - 10.0 / 11.0).abs() < 1e-12);
+        for difference in [-300.0, -42.0, 0.0, 150.0, 600.0] {
+            assert!((elo(expected_score(difference)) - difference).abs() < 1e-9);
+        }
+        assert_eq!(elo(0.0), -MAX_ELO);
+        assert_eq!(elo(1.0), MAX_ELO);
+    }
+
+    #[test]
+    fn elo_of_standings_with_its_error() {
+        let (difference, error) = standings(60, 20, 20).elo();
+        // A score of 0.7
+        assert!((difference - 147.2).abs() < 0.1);
+        assert!(error > 0.0 && error < difference);
+
+        let (difference, error) = standings(50, 0, 50).elo();
+        assert_eq!(difference, 0.0);
+        assert!(error > 60.0 && error < 80.0);
+    }
+
+    #[test]
+    fn llr_weighs_both_hypotheses_the_same() {
+        let results = standings(120, 30, 90);
+        assert!(results.llr(0.0, 10.0) > 0.0);
+        assert!((results.llr(0.0, 10.0) + results.llr(10.0, 0.0)).abs() < 1e-9);
+        assert!(standings(90, 30, 120).llr(0.0, 10.0) < 0.0);
+    }
+
+    #[test]
+    fn sprt_bounds_and_decisions() {
+        let sprt = Sprt::new(0.0, 10.0, 0.05, 0.05);
+        assert!((sprt.lower - (0.05f64 / 0.95).ln()).abs() < 1e-12);
+        assert!((sprt.upper + sprt.lower).abs() < 1e-12);
+
+        assert_eq!(sprt.decision(&standings(0, 0, 0)), None);
+        assert_eq!(sprt.decision(&standings(300, 0, 200)), None);
+        assert_eq!(sprt.decision(&standings(600, 0, 400)), Some(true));
+        // Even, with enough draws to be sure it is not 10 Elo better
+        assert_eq!(sprt.decision(&standings(1000, 3000, 1000)), Some(false));
+    }
+}
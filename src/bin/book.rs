@@ -0,0 +1,68 @@
+//! Fills the opening book by playing the first days of self-play games on
+//! random maps, with a long UCT search for every decision of both players.
+//!
+//! Usage: cargo run --release --bin book -- --fresh --games 60 --days 2 --think-ms 5000
+//!
+//! New positions are added to the embedded book, or to an empty one with
+//! `--fresh`, and the result is written to src/opening_book_data.rs, or to the
+//! file given with `--out`. The book records the shortest think time of its
+//! moves, the bot only consulting it on turns with less time than that.
+
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use rustbot::config::{flag, parsed_setting, setting};
+use rustbot::game::Action;
+use rustbot::mapgen::{generate_board, initial_state};
+use rustbot::opening_book::OpeningBook;
+use rustbot::rng::Xorshift;
+use rustbot::rules::{apply_joint_actions, legal_actions};
+use rustbot::time::Deadline;
+use rustbot::uct::{Uct, UctConfig};
+
+fn main() {
+    let games: usize = parsed_setting("games").unwrap_or(20);
+    let days: i32 = parsed_setting("days").unwrap_or(3);
+    let think = Duration::from_millis(parsed_setting("think-ms").unwrap_or(1000));
+    let out = setting("out").unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("src/opening_book_data.rs").display().to_string());
+
+    let (mut rng, seed) = Xorshift::from_settings();
+    eprintln!("Seed: {}", seed);
+
+    let mut book = if flag("fresh") { OpeningBook::default() } else { OpeningBook::embedded() };
+    let initial_size = book.len();
+    book.think = if book.is_empty() { think } else { book.think.min(think) };
+
+    for game in 0..games {
+        let board = generate_board(&mut rng);
+        let mut state = initial_state(&board, &mut rng);
+        let mut searches = [Uct::new(UctConfig::default()), Uct::new(UctConfig::default())];
+
+        while !state.is_game_over() && state.day < days {
+            let mut actions = [Action::Wait; 2];
+            for (player, search) in searches.iter_mut().enumerate() {
+                if state.waiting[player] {
+                    continue;
+                }
+                // Every decision is made and stored as player 0
+                let view = if player == 0 { state.clone() } else { state.swapped() };
+                let legal = legal_actions(&view, &board, 0);
+                let result = search.search(&view, &board, &legal, &Deadline::after(think), &mut rng);
+                book.insert(&view, result.action);
+                actions[player] = result.action;
+            }
+            apply_joint_actions(&mut state, &board, actions);
+        }
+
+        eprintln!("Game {}/{}: {} positions in the book", game + 1, games, book.len());
+    }
+
+    let source = format!(
+        "// Generated by `cargo run --release --bin book`, do not edit.\npub const THINK_MS: u64 = {};\npub const BOOK: &str = \"{}\";\n",
+        book.think.as_millis(),
+        book.encode()
+    );
+    fs::write(&out, source).unwrap_or_else(|e| panic!("Failed to write {}: {}", out, e));
+    eprintln!("Wrote {} positions ({} new) to {}", book.len(), book.len() - initial_size, out);
+}
@@ -0,0 +1,42 @@
+//! Produces the single source file submitted to CodinGame: the modules listed
+//! in lib.rs are inlined after the bot's main.rs.
+//!
+//! Usage: cargo run --bin bundle > submission.rs
+//!
+//! With `--weights file`, the evaluation weights in the file are baked into
+//! the bundle in place of those of weights_data.rs.
+
+use std::fs;
+use std::path::Path;
+
+use rustbot::config::setting;
+use rustbot::weights::EvalWeights;
+
+fn main() {
+    let weights = setting("weights").map(|path| {
+        let text = fs::read_to_string(&path).unwrap_or_else(|e| panic!("Failed to read {}: {}", path, e));
+        // Checked now rather than when the submitted bot starts
+        EvalWeights::default().apply(&text).unwrap_or_else(|e| panic!("Invalid weights in {}: {}", path, e));
+        text
+    });
+
+    let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    let main = fs::read_to_string(src.join("main.rs")).expect("Failed to read main.rs");
+    let lib = fs::read_to_string(src.join("lib.rs")).expect("Failed to read lib.rs");
+
+    let mut bundle = main.replace("rustbot::", "crate::");
+
+    for line in lib.lines() {
+        let name = match line.trim().strip_prefix("pub mod ").and_then(|l| l.strip_suffix(';')) {
+            Some(name) => name,
+            None => continue,
+        };
+        let module = match (name, &weights) {
+            ("weights_data", Some(text)) => format!("pub const WEIGHTS: &str = {:?};\n", text),
+            _ => fs::read_to_string(src.join(format!("{}.rs", name))).unwrap_or_else(|_| panic!("Failed to read module {}", name)),
+        };
+        bundle.push_str(&format!("\npub mod {} {{\n{}}}\n", name, module));
+    }
+
+    print!("{}", bundle);
+}
@@ -0,0 +1,54 @@
+//! Replays a recorded game: every turn of the transcript is drawn and the
+//! bot decides on it again, in order, so that its state carries over from
+//! turn to turn as in the game.
+//!
+//! Usage: cargo run --release --bin replay -- --input game-1.txt --bot uct --shadows
+//!
+//! Transcripts are the input of one bot, see `--log` of the arena. The bot
+//! gets the usual time limits, or `--iterations` per turn. `--ascii` drops
+//! the colors and `--shadows` shows the cells shaded on the next day. With
+//! `--svg dir`, every turn is also drawn with the bot's decision to
+//! dir/turn-NNN.svg.
+
+use std::fs;
+use std::path::Path;
+
+use rustbot::bot::bot_from_settings;
+use rustbot::config::{parsed_setting, setting};
+use rustbot::render::{render, RenderOptions};
+use rustbot::rng::seed_from_settings;
+use rustbot::svg::render_svg;
+use rustbot::time::TimeManager;
+use rustbot::transcript::Transcript;
+
+fn main() {
+    let input = setting("input").unwrap_or_else(|| panic!("Missing the transcript, --input"));
+    let transcript = Transcript::open(&input);
+    let options = RenderOptions::from_settings();
+    let svg_dir = setting("svg");
+    if let Some(dir) = &svg_dir {
+        fs::create_dir_all(dir).unwrap_or_else(|e| panic!("Failed to create {}: {}", dir, e));
+    }
+
+    let seed = seed_from_settings();
+    eprintln!("Seed: {}", seed);
+    let max_iterations = parsed_setting("iterations");
+
+    let mut bot = bot_from_settings(seed);
+    bot.init(&transcript.board);
+    let mut time_manager = TimeManager::default();
+
+    for (number, turn) in transcript.turns.iter().enumerate() {
+        print!("{}", render(&turn.state, &transcript.board, &options));
+
+        let deadline = time_manager.start_turn(turn.state.day).with_max_iterations(max_iterations);
+        let decision = bot.decide(&turn.state, &turn.actions, deadline);
+        println!("Turn {}: {} {} Time: {}\n", number, decision.action, decision.message, time_manager.end_turn().as_millis());
+
+        if let Some(dir) = &svg_dir {
+            let path = Path::new(dir).join(format!("turn-{:03}.svg", number));
+            fs::write(&path, render_svg(&turn.state, &transcript.board, Some(&decision), options.shadows))
+                .unwrap_or_else(|e| panic!("Failed to write {}: {}", path.display(), e));
+        }
+    }
+}
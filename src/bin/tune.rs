@@ -0,0 +1,228 @@
+//! Tunes the evaluation weights and the playout settings by SPSA
+//! (simultaneous perturbation stochastic approximation) over self-play.
+//!
+//! Usage: cargo run --release --bin tune -- --rounds 200 --games 16 --iterations 200 --out tuned.txt
+//!
+//! Every round perturbs all parameters at once in a random direction and
+//! plays the two opposite perturbations against each other, in pairs of
+//! games with swapped seats on random maps, spread over `--threads` threads.
+//! The parameters then move along the perturbation in proportion to the
+//! result. Parameters are handled in units of their step, and integer
+//! weights are rounded when played.
+//!
+//! Searches are limited to `--iterations` per decision rather than time so
+//! that the number of threads does not change the strength of the bots. The
+//! tuned parameters are finally played against the starting ones, and
+//! written with that win rate in the weights file format to `--out`.
+//! Tuning starts from the weights given with `--weights`, the horizon and
+//! temperature included, and the `--horizon` and `--temperature` settings.
+
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use rand::{Rng, SeedableRng};
+
+use rustbot::bot::{Bot, UctBot};
+use rustbot::config::{parsed_setting, setting};
+use rustbot::mapgen::{generate_board, initial_state};
+use rustbot::rng::Xorshift;
+use rustbot::rollout::PlayoutConfig;
+use rustbot::selfplay::play_game;
+use rustbot::time::Deadline;
+use rustbot::uct::UctConfig;
+
+// Stability constant and decay exponents of the SPSA gain sequences, the
+// values recommended by Spall
+const STABILITY_SHARE: f64 = 0.1;
+const RATE_DECAY: f64 = 0.602;
+const PERTURBATION_DECAY: f64 = 0.101;
+
+struct Parameter {
+    name: &'static str,
+    step: f64,
+    min: f64,
+    max: f64,
+}
+
+const fn parameter(name: &'static str, step: f64, min: f64, max: f64) -> Parameter {
+    Parameter { name, step, min, max }
+}
+
+// The weights of EvalWeights, the playouts' horizon and temperature last.
+// greedy_complete_from_day only drives the greedy bot, which self-play does
+// not play, and is written out as it started.
+const PARAMETERS: [Parameter; 19] = [
+    parameter("grow_seed_bonus", 2.0, 0.0, 30.0),
+    parameter("grow_late_day", 1.0, 12.0, 24.0),
+    parameter("grow_late_base", 1.0, 0.0, 15.0),
+    parameter("complete_late_day", 1.0, 12.0, 24.0),
+    parameter("complete_late_bonus", 2.0, 0.0, 30.0),
+    parameter("harvest_bonus_after_day", 1.0, 5.0, 23.0),
+    parameter("seed_empty_neighbor_penalty", 1.0, 0.0, 6.0),
+    parameter("seed_center_divisor", 2.0, 1.0, 40.0),
+    parameter("seed_richness", 1.0, 0.0, 6.0),
+    parameter("sun_as_points_until_day", 1.0, 0.0, 24.0),
+    parameter("tree_maturity_0", 0.1, 0.0, 1.5),
+    parameter("tree_maturity_1", 0.1, 0.0, 1.5),
+    parameter("tree_maturity_2", 0.1, 0.0, 1.5),
+    parameter("tree_maturity_3", 0.1, 0.0, 1.5),
+    parameter("sun_income_share", 0.1, 0.0, 1.5),
+    parameter("seed_max_trees", 1.0, 2.0, 16.0),
+    parameter("seed_until_day", 1.0, 5.0, 20.0),
+    parameter("horizon", 1.0, 1.0, 24.0),
+    parameter("temperature", 0.5, 0.1, 20.0),
+];
+
+// Parameter values, in units of each parameter's step
+fn position(playout: &PlayoutConfig) -> Vec<f64> {
+    PARAMETERS
+        .iter()
+        .map(|parameter| playout.weights.get(parameter.name).unwrap() / parameter.step)
+        .collect()
+}
+
+fn playout_at(position: &[f64]) -> PlayoutConfig {
+    let mut playout = PlayoutConfig::default();
+    for (parameter, x) in PARAMETERS.iter().zip(position) {
+        playout.weights.set(parameter.name, (x * parameter.step).clamp(parameter.min, parameter.max));
+    }
+    playout
+}
+
+fn clamp_position(position: &mut [f64]) {
+    for (parameter, x) in PARAMETERS.iter().zip(position.iter_mut()) {
+        *x = x.clamp(parameter.min / parameter.step, parameter.max / parameter.step);
+    }
+}
+
+struct Match {
+    games: usize,
+    iterations: i32,
+    threads: usize,
+}
+
+#[derive(Default, Debug)]
+struct Results {
+    wins: usize,
+    draws: usize,
+    losses: usize,
+}
+
+impl Results {
+    fn games(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+
+    // Wins minus losses per game, in [-1, 1]
+    fn balance(&self) -> f64 {
+        (self.wins as f64 - self.losses as f64) / self.games().max(1) as f64
+    }
+
+    fn win_rate(&self) -> f64 {
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games().max(1) as f64
+    }
+}
+
+// Plays `challenger` against `reference` in pairs of games with swapped seats
+fn play_match(challenger: PlayoutConfig, reference: PlayoutConfig, settings: &Match, seed: u64) -> Results {
+    let next_game = AtomicUsize::new(0);
+    let results = Mutex::new(Results::default());
+    let pairs = (settings.games + 1) / 2;
+
+    thread::scope(|scope| {
+        for _ in 0..settings.threads {
+            scope.spawn(|| loop {
+                let pair = next_game.fetch_add(1, Ordering::Relaxed);
+                if pair >= pairs {
+                    break;
+                }
+                // Both games of a pair are on the same map
+                let mut rng = Xorshift::seed_from_u64(seed.wrapping_add(pair as u64));
+                let board = generate_board(&mut rng);
+                let state = initial_state(&board, &mut rng);
+
+                for challenger_seat in 0..2 {
+                    let configs = if challenger_seat == 0 { [challenger, reference] } else { [reference, challenger] };
+                    let mut bots: [Box<dyn Bot>; 2] = [
+                        Box::new(UctBot::new(UctConfig { playout: configs[0], ..UctConfig::default() }, rng.gen())),
+                        Box::new(UctBot::new(UctConfig { playout: configs[1], ..UctConfig::default() }, rng.gen())),
+                    ];
+                    let deadline = || Deadline::after(Duration::from_secs(60)).with_max_iterations(Some(settings.iterations));
+                    let end = play_game(&board, state.clone(), &mut bots, deadline);
+
+                    let mut results = results.lock().unwrap();
+                    match end.winner() {
+                        Some(winner) if winner == challenger_seat => results.wins += 1,
+                        Some(_) => results.losses += 1,
+                        None => results.draws += 1,
+                    }
+                }
+            });
+        }
+    });
+
+    results.into_inner().unwrap()
+}
+
+fn main() {
+    let rounds: usize = parsed_setting("rounds").unwrap_or(100);
+    let threads = parsed_setting("threads").unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+    let settings = Match { games: parsed_setting("games").unwrap_or(16), iterations: parsed_setting("iterations").unwrap_or(200), threads };
+    let verify = Match { games: parsed_setting("verify").unwrap_or(200), ..settings };
+    // gain of the parameter moves and size of the perturbations, in steps
+    let rate: f64 = parsed_setting("rate").unwrap_or(1.0);
+    let perturbation: f64 = parsed_setting("perturbation").unwrap_or(1.0);
+    let out = setting("out").unwrap_or_else(|| "tuned.txt".to_string());
+
+    let (mut rng, seed) = Xorshift::from_settings();
+    eprintln!("Seed: {}", seed);
+
+    let start = PlayoutConfig::from_settings();
+    let mut x = position(&start);
+    let stability = STABILITY_SHARE * rounds as f64;
+
+    for round in 0..rounds {
+        let a = rate * (1.0 + stability).powf(RATE_DECAY) / (round as f64 + 1.0 + stability).powf(RATE_DECAY);
+        let c = perturbation / (round as f64 + 1.0).powf(PERTURBATION_DECAY);
+        let delta: Vec<f64> = PARAMETERS.iter().map(|_| if rng.gen() { 1.0 } else { -1.0 }).collect();
+
+        let plus: Vec<f64> = x.iter().zip(&delta).map(|(x, d)| x + c * d).collect();
+        let minus: Vec<f64> = x.iter().zip(&delta).map(|(x, d)| x - c * d).collect();
+        let results = play_match(playout_at(&plus), playout_at(&minus), &settings, rng.gen());
+
+        // Gradient estimate (f(x + c delta) - f(x - c delta)) / (2 c delta)
+        // with the match balance standing for the difference
+        let balance = results.balance();
+        for (x, d) in x.iter_mut().zip(&delta) {
+            *x += a * balance / (2.0 * c * d);
+        }
+        clamp_position(&mut x);
+
+        eprintln!("Round {}/{}: {}-{}-{}", round + 1, rounds, results.wins, results.draws, results.losses);
+    }
+
+    let tuned = playout_at(&x);
+    let results = play_match(tuned, start, &verify, rng.gen());
+    eprintln!("Against the start: {}-{}-{}, win rate {:.3}", results.wins, results.draws, results.losses, results.win_rate());
+
+    let text = format!(
+        "# Tuned by `cargo run --release --bin tune` with seed {}: {} rounds of {} games at {} iterations\n\
+         # win rate against the starting parameters: {:.3} ({}-{}-{} in {} games)\n\
+         {}",
+        seed,
+        rounds,
+        settings.games,
+        settings.iterations,
+        results.win_rate(),
+        results.wins,
+        results.draws,
+        results.losses,
+        results.games(),
+        tuned.weights
+    );
+    fs::write(&out, text).unwrap_or_else(|e| panic!("Failed to write {}: {}", out, e));
+    eprintln!("Wrote {}", out);
+}
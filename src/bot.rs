@@ -0,0 +1,205 @@
+//! The decision makers behind the game loop. Each bot gets the board once,
+//! then a position, the legal actions of the referee and a deadline every
+//! turn. The one playing is chosen with `--bot` or RUSTBOT_BOT, so that the
+//! search variants can be compared against each other and the baselines.
+
+use rand::seq::SliceRandom;
+
+use crate::config::{flag, setting};
+use crate::day_plan::{DayPlanConfig, DayPlanner};
+use crate::endgame::{EndgameConfig, EndgameSearch};
+use crate::flat_mc::{playout_moves, FlatMcConfig};
+use crate::game::{Action, Area};
+use crate::greedy::GreedyBot;
+use crate::opening_book::OpeningBook;
+use crate::opponent_model::OpponentModel;
+use crate::rng::Xorshift;
+use crate::rules::State;
+use crate::time::Deadline;
+use crate::tracker::OpponentTracker;
+use crate::uct::{Uct, UctConfig};
+use crate::weights::EvalWeights;
+
+pub const BOT_NAMES: [&str; 5] = ["uct", "flat", "plan", "greedy", "random"];
+
+pub struct Decision {
+    pub action: Action,
+    // appended to the action, shown next to the tree in the replays
+    pub message: String,
+}
+
+impl Decision {
+    pub fn new(action: Action, message: String) -> Decision {
+        Decision { action, message }
+    }
+}
+
+pub trait Bot {
+    fn init(&mut self, board: &Area);
+
+    fn decide(&mut self, state: &State, actions: &[Action], deadline: Deadline) -> Decision;
+}
+
+pub fn create_bot(name: &str, seed: u64) -> Option<Box<dyn Bot>> {
+    let bot: Box<dyn Bot> = match name {
+        "uct" => Box::new(UctBot::new(UctConfig::from_settings(), seed)),
+        "flat" => Box::new(FlatMcBot::new(seed)),
+        "plan" => Box::new(DayPlanBot::new()),
+        "greedy" => Box::new(GreedyBot::default()),
+        "random" => Box::new(RandomBot::new(seed)),
+        _ => return None,
+    };
+    Some(bot)
+}
+
+// The bot named by the `bot` setting, `day-plan` being kept as an alias of
+// `--bot plan`
+pub fn bot_from_settings(seed: u64) -> Box<dyn Bot> {
+    let name = setting("bot").unwrap_or_else(|| if flag("day-plan") { "plan" } else { "uct" }.to_string());
+    create_bot(&name, seed).unwrap_or_else(|| panic!("Unknown bot {}, expected one of {}", name, BOT_NAMES.join(", ")))
+}
+
+/// Decoupled UCT, behind the opening book and the endgame search, with the
+/// playouts biased by what the opponent has been seen doing.
+pub struct UctBot {
+    board: Area,
+    uct: Uct,
+    book: OpeningBook,
+    endgame: EndgameSearch,
+    tracker: OpponentTracker,
+    opponent_model: OpponentModel,
+    rng: Xorshift,
+    debug: bool,
+}
+
+impl UctBot {
+    pub fn new(config: UctConfig, seed: u64) -> UctBot {
+        UctBot {
+            board: Area::new(),
+            uct: Uct::new(config),
+            book: OpeningBook::embedded(),
+            endgame: EndgameSearch::new(EndgameConfig::from_settings()),
+            tracker: OpponentTracker::default(),
+            opponent_model: OpponentModel::default(),
+            rng: Xorshift::new(seed),
+            debug: flag("debug"),
+        }
+    }
+
+    // Book, then endgame search, then UCT
+    fn choose(&mut self, state: &State, actions: &[Action], deadline: Deadline) -> Decision {
+        if self.book.applies(deadline.remaining()) {
+            if let Some(action) = self.book.lookup(state, actions) {
+                return Decision::new(action, "book".to_string());
+            }
+        }
+
+        // Half the turn for the endgame search, the rest for a fallback if it does not finish
+        if self.endgame.applies(state) {
+            if let Some((action, value)) = self.endgame.search(state, &self.board, actions, &deadline.share(0, 0.5)) {
+                return Decision::new(action, format!("bound: {}", value));
+            }
+        }
+
+        let result = self.uct.search(state, &self.board, actions, &deadline, &mut self.rng);
+        Decision::new(
+            result.action,
+            format!("score: {:.3} visits: {} choices: {} ({}) Rolls: {} Reused: {}", result.value, result.visits, result.choices, actions.len(), result.iterations, result.reused),
+        )
+    }
+}
+
+impl Bot for UctBot {
+    fn init(&mut self, board: &Area) {
+        self.board = board.clone();
+    }
+
+    fn decide(&mut self, state: &State, actions: &[Action], deadline: Deadline) -> Decision {
+        if let Some(observed) = self.tracker.observe(state, &self.board) {
+            self.opponent_model.update(observed, &self.board);
+            if self.debug {
+                eprintln!("Opponent {} ({:?})", observed, self.opponent_model.bias());
+            }
+        }
+        self.uct.config.playout.opponent = Some(self.opponent_model.bias());
+
+        let decision = self.choose(state, actions, deadline);
+        self.tracker.played(decision.action);
+        self.uct.follow(state, &self.board, decision.action);
+        decision
+    }
+}
+
+/// Flat Monte Carlo over our first action.
+pub struct FlatMcBot {
+    board: Area,
+    config: FlatMcConfig,
+    rng: Xorshift,
+}
+
+impl FlatMcBot {
+    pub fn new(seed: u64) -> FlatMcBot {
+        FlatMcBot { board: Area::new(), config: FlatMcConfig::from_settings(), rng: Xorshift::new(seed) }
+    }
+}
+
+impl Bot for FlatMcBot {
+    fn init(&mut self, board: &Area) {
+        self.board = board.clone();
+    }
+
+    fn decide(&mut self, state: &State, actions: &[Action], deadline: Deadline) -> Decision {
+        let (action, mean, choices, rolls) = playout_moves(state, &self.board, actions, &self.config, &deadline, &mut self.rng);
+        Decision::new(action, format!("score: {:.1} choices: {} ({}) Rolls: {}", mean, choices, actions.len(), rolls))
+    }
+}
+
+/// Beam search over our whole day, the opponent assumed idle.
+pub struct DayPlanBot {
+    board: Area,
+    planner: DayPlanner,
+}
+
+impl DayPlanBot {
+    pub fn new() -> DayPlanBot {
+        DayPlanBot { board: Area::new(), planner: DayPlanner::new(DayPlanConfig { weights: EvalWeights::from_settings(), ..DayPlanConfig::default() }) }
+    }
+}
+
+impl Default for DayPlanBot {
+    fn default() -> Self {
+        DayPlanBot::new()
+    }
+}
+
+impl Bot for DayPlanBot {
+    fn init(&mut self, board: &Area) {
+        self.board = board.clone();
+    }
+
+    fn decide(&mut self, state: &State, actions: &[Action], deadline: Deadline) -> Decision {
+        let (action, value) = self.planner.decide(state, &self.board, actions, &deadline);
+        let plan: Vec<String> = self.planner.plan().iter().map(|a| a.to_string()).collect();
+        Decision::new(action, format!("value: {:?} then: {}", value, plan.join(", ")))
+    }
+}
+
+/// A uniformly random legal action.
+pub struct RandomBot {
+    rng: Xorshift,
+}
+
+impl RandomBot {
+    pub fn new(seed: u64) -> RandomBot {
+        RandomBot { rng: Xorshift::new(seed) }
+    }
+}
+
+impl Bot for RandomBot {
+    fn init(&mut self, _board: &Area) {}
+
+    fn decide(&mut self, _state: &State, actions: &[Action], _deadline: Deadline) -> Decision {
+        let action = actions.choose(&mut self.rng).copied().unwrap_or(Action::Wait);
+        Decision::new(action, String::new())
+    }
+}
@@ -0,0 +1,28 @@
+//! Settings for local runs, read from `--name value` command line flags or
+//! `RUSTBOT_NAME` environment variables. In the arena neither is set and the
+//! defaults apply.
+
+use std::env;
+use std::str::FromStr;
+
+pub fn setting(name: &str) -> Option<String> {
+    let flag = format!("--{}", name);
+    let mut args = env::args().skip_while(|arg| *arg != flag);
+    if args.next().is_some() {
+        if let Some(value) = args.next() {
+            return Some(value);
+        }
+    }
+    env::var(format!("RUSTBOT_{}", name.to_uppercase().replace('-', "_"))).ok()
+}
+
+// Switch given as a bare `--name` flag or a RUSTBOT_NAME variable other than 0
+pub fn flag(name: &str) -> bool {
+    let flag = format!("--{}", name);
+    env::args().any(|arg| arg == flag) || setting(name).map_or(false, |value| value != "0")
+}
+
+// Parsed setting, panicking on malformed values so typos do not go unnoticed
+pub fn parsed_setting<T: FromStr>(name: &str) -> Option<T> {
+    setting(name).map(|value| value.parse().unwrap_or_else(|_| panic!("Invalid value for {}: {}", name, value)))
+}
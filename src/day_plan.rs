@@ -0,0 +1,150 @@
+//! Planning of our whole day: a beam search over sequences of our actions
+//! ending with WAIT, with the opponent assumed idle. Within a day the order
+//! of actions only matters through cost scaling and sun, so each plan is
+//! scored once the day is over by the action heuristics and the leaf
+//! evaluation. The best plan is kept and followed on the next turns of the
+//! day as long as it stays legal.
+
+use std::collections::HashSet;
+
+use crate::game::{Action, Area, FixedState};
+use crate::heuristics::evaluate_leaf;
+use crate::rollout::{seeds_allowed, Rollout};
+use crate::rules::{possible_actions, State};
+use crate::time::Deadline;
+use crate::weights::EvalWeights;
+
+#[derive(Copy, Clone)]
+pub struct DayPlanConfig {
+    pub beam_width: usize,
+    // longest plan considered, WAIT included
+    pub max_actions: usize,
+    pub weights: EvalWeights,
+}
+
+impl Default for DayPlanConfig {
+    fn default() -> Self {
+        DayPlanConfig { beam_width: 20, max_actions: 8, weights: EvalWeights::baked() }
+    }
+}
+
+#[derive(Clone)]
+struct Plan {
+    actions: Vec<Action>,
+    rollout: Rollout,
+    value: i32,
+}
+
+impl Plan {
+    fn evaluate(&mut self, board: &Area, weights: &EvalWeights) {
+        self.value = self.rollout.points[0];
+        if !self.rollout.state.is_game_over() {
+            self.value += evaluate_leaf(&self.rollout.state, board, 0, weights);
+        }
+    }
+}
+
+pub struct DayPlanner {
+    config: DayPlanConfig,
+    // rest of the plan being followed, with the day and sun it expects
+    plan: Vec<Action>,
+    expected: Option<(i32, i32)>,
+}
+
+impl DayPlanner {
+    pub fn new(config: DayPlanConfig) -> DayPlanner {
+        DayPlanner { config, plan: vec![], expected: None }
+    }
+
+    // Returns the action to play and the value of the plan it comes from, or
+    // None as value when following a cached plan
+    pub fn decide(&mut self, state: &State, board: &Area, expected_actions: &[Action], deadline: &Deadline) -> (Action, Option<i32>) {
+        if self.expected == Some((state.day, state.sun[0])) {
+            if let Some(action) = self.plan.first().copied().filter(|action| expected_actions.contains(action)) {
+                self.follow(state, board);
+                return (action, None);
+            }
+        }
+
+        let best = self.search(state, board, expected_actions, deadline);
+        self.plan = best.actions;
+        let action = self.plan[0];
+        self.follow(state, board);
+        (action, Some(best.value))
+    }
+
+    // Drops the next action of the plan and remembers the position it leads to
+    fn follow(&mut self, state: &State, board: &Area) {
+        let action = self.plan.remove(0);
+        let mut rollout = Rollout::new(state.clone());
+        rollout.step(board, &self.config.weights, [action, Action::Null]);
+        self.expected = Some((rollout.state.day, rollout.state.sun[0]));
+    }
+
+    pub fn plan(&self) -> &[Action] {
+        &self.plan
+    }
+
+    fn search(&self, state: &State, board: &Area, expected_actions: &[Action], deadline: &Deadline) -> Plan {
+        let mut beam = vec![Plan { actions: vec![], rollout: Rollout::new(state.clone()), value: 0 }];
+        let mut best: Option<Plan> = None;
+        let mut expansions = 0;
+
+        // The deadline is checked before every expansion, as a single level
+        // can hold hundreds of plans
+        'search: while !beam.is_empty() {
+            let mut next_beam = vec![];
+            // plans with the same actions in another order are only kept once
+            let mut seen: HashSet<Vec<u16>, FixedState> = HashSet::default();
+
+            for plan in &beam {
+                let mut choices = possible_actions(&plan.rollout.state, board, 0, seeds_allowed(&plan.rollout.state, 0, &self.config.weights));
+                choices.retain(|action| *action != Action::Wait);
+                if plan.actions.len() + 1 >= self.config.max_actions {
+                    choices.clear();
+                }
+                choices.push(Action::Wait);
+                if plan.actions.is_empty() {
+                    choices.retain(|action| expected_actions.contains(action));
+                }
+
+                for action in choices {
+                    if deadline.reached(expansions) {
+                        break 'search;
+                    }
+                    expansions += 1;
+                    let mut next = plan.clone();
+                    next.actions.push(action);
+
+                    if action == Action::Wait {
+                        // End of our day, the opponent is assumed to wait too
+                        next.rollout.step(board, &self.config.weights, [Action::Wait, Action::Wait]);
+                        next.evaluate(board, &self.config.weights);
+                        if best.as_ref().map_or(true, |best| next.value > best.value) {
+                            best = Some(next);
+                        }
+                        continue;
+                    }
+
+                    let mut key: Vec<u16> = next.actions.iter().map(|action| action.code()).collect();
+                    key.sort_unstable();
+                    if !seen.insert(key) {
+                        continue;
+                    }
+                    next.rollout.step(board, &self.config.weights, [action, Action::Null]);
+                    next.evaluate(board, &self.config.weights);
+                    next_beam.push(next);
+                }
+            }
+
+            next_beam.sort_by_key(|plan| -plan.value);
+            next_beam.truncate(self.config.beam_width);
+            beam = next_beam;
+        }
+
+        // Out of time before any plan reached the end of the day, the best of
+        // the last complete level is followed instead
+        best.or_else(|| beam.into_iter().filter(|plan| !plan.actions.is_empty()).max_by_key(|plan| plan.value))
+            .unwrap_or_else(|| Plan { actions: vec![Action::Wait], rollout: Rollout::new(state.clone()), value: 0 })
+    }
+}
@@ -0,0 +1,264 @@
+//! Full depth search of the last days of the game.
+//!
+//! Simultaneous turns are searched as if the opponent answered knowing our
+//! action: alpha-beta over max(ours) min(theirs), with a transposition table
+//! kept between turns.
+//!
+//! Only COMPLETE, GROW and WAIT are considered for both players. Seeds
+//! cannot grow into points this late, but they still spend sun, make their
+//! parent dormant and count in the tree tiebreak, and with their dozens of
+//! targets the search would not finish within a turn. The value found is
+//! therefore a heuristic bound, the worst case against an opponent who does
+//! not seed, and not a guarantee against any opponent.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crate::config::parsed_setting;
+use crate::game::{Action, Area};
+use crate::rules::{apply_joint_actions, calculate_complete_actions, calculate_grow_actions, State};
+use crate::time::Deadline;
+
+// Score points are worth more than any difference in tree count, which only
+// breaks ties
+const SCORE_WEIGHT: i32 = 100;
+const MAX_TABLE_SIZE: usize = 2_000_000;
+// With an iteration budget, a search iteration being a whole playout
+const NODES_PER_ITERATION: i32 = 1000;
+
+#[derive(Copy, Clone)]
+pub struct EndgameConfig {
+    // first day the solver is used on
+    pub from_day: i32,
+}
+
+impl Default for EndgameConfig {
+    fn default() -> Self {
+        EndgameConfig { from_day: 21 }
+    }
+}
+
+impl EndgameConfig {
+    // Defaults overridden by the `endgame-day` setting
+    pub fn from_settings() -> EndgameConfig {
+        EndgameConfig { from_day: parsed_setting("endgame-day").unwrap_or(EndgameConfig::default().from_day) }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Copy, Clone)]
+struct Entry {
+    value: i32,
+    bound: Bound,
+    action: Action,
+}
+
+pub struct EndgameSearch {
+    pub config: EndgameConfig,
+    table: HashMap<u64, Entry>,
+    nodes: i32,
+}
+
+impl EndgameSearch {
+    pub fn new(config: EndgameConfig) -> EndgameSearch {
+        EndgameSearch { config, table: HashMap::new(), nodes: 0 }
+    }
+
+    pub fn applies(&self, state: &State) -> bool {
+        state.day >= self.config.from_day
+    }
+
+    // Best action and the bound on the final score difference, or None if the
+    // search could not finish before the deadline. A tree count difference
+    // only shows in the choice of the action.
+    pub fn search(&mut self, state: &State, board: &Area, expected_actions: &[Action], deadline: &Deadline) -> Option<(Action, i32)> {
+        if self.table.len() > MAX_TABLE_SIZE {
+            self.table.clear();
+        }
+        self.nodes = 0;
+
+        let mut ours = candidate_actions(state, 0);
+        ours.retain(|action| expected_actions.contains(action));
+        if ours.is_empty() {
+            ours.push(Action::Wait);
+        }
+
+        let mut best = (ours[0], i32::MIN);
+        for action in ours {
+            let value = self.min_value(state, board, action, best.1, i32::MAX, deadline)?;
+            if value > best.1 {
+                best = (action, value);
+            }
+        }
+        Some((best.0, score_margin(best.1)))
+    }
+
+    // Value of the position for us, within the (alpha, beta) window
+    fn max_value(&mut self, state: &State, board: &Area, mut alpha: i32, beta: i32, deadline: &Deadline) -> Option<i32> {
+        if state.is_game_over() {
+            return Some(terminal_value(state));
+        }
+
+        self.nodes += 1;
+        if self.nodes % 1024 == 0 && deadline.reached(self.nodes / NODES_PER_ITERATION) {
+            return None;
+        }
+
+        let key = position_key(state);
+        let mut ours = candidate_actions(state, 0);
+        if let Some(entry) = self.table.get(&key) {
+            match entry.bound {
+                Bound::Exact => return Some(entry.value),
+                Bound::Lower if entry.value >= beta => return Some(entry.value),
+                Bound::Upper if entry.value <= alpha => return Some(entry.value),
+                _ => (),
+            }
+            // Try the best action found last time first
+            if let Some(i) = ours.iter().position(|action| *action == entry.action) {
+                ours.swap(0, i);
+            }
+        }
+
+        let original_alpha = alpha;
+        let mut best = (Action::Wait, i32::MIN);
+        for action in ours {
+            let value = self.min_value(state, board, action, alpha, beta, deadline)?;
+            if value > best.1 {
+                best = (action, value);
+            }
+            alpha = alpha.max(value);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best.1 <= original_alpha {
+            Bound::Upper
+        } else if best.1 >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table.insert(key, Entry { value: best.1, bound, action: best.0 });
+        Some(best.1)
+    }
+
+    // Worst value over the opponent's answers to our action
+    fn min_value(&mut self, state: &State, board: &Area, action: Action, alpha: i32, mut beta: i32, deadline: &Deadline) -> Option<i32> {
+        let mut worst = i32::MAX;
+        for answer in candidate_actions(state, 1) {
+            let mut next = state.clone();
+            apply_joint_actions(&mut next, board, [action, answer]);
+            let value = self.max_value(&next, board, alpha, beta, deadline)?;
+            worst = worst.min(value);
+            beta = beta.min(value);
+            if worst <= alpha {
+                break;
+            }
+        }
+        Some(worst)
+    }
+}
+
+// COMPLETE first, then the biggest trees to grow, then WAIT
+fn candidate_actions(state: &State, player: usize) -> Vec<Action> {
+    if state.waiting[player] {
+        return vec![Action::Wait];
+    }
+
+    let sun = state.sun[player];
+    let mut actions = calculate_complete_actions(sun, &state.forest, player);
+    let mut grow = calculate_grow_actions(sun, &state.forest, state.trees_counts[player], player);
+    grow.sort_by_key(|action| match action {
+        Action::Grow(cell) => -state.forest[cell].size,
+        _ => 0,
+    });
+    actions.append(&mut grow);
+    actions.push(Action::Wait);
+    actions
+}
+
+// Score difference of a value, rounded as the tree difference is below half
+// a score point either way
+fn score_margin(value: i32) -> i32 {
+    (value + SCORE_WEIGHT / 2).div_euclid(SCORE_WEIGHT)
+}
+
+fn terminal_value(state: &State) -> i32 {
+    (state.score[0] - state.score[1]) * SCORE_WEIGHT + state.total_trees(0) - state.total_trees(1)
+}
+
+fn position_key(state: &State) -> u64 {
+    let mut trees: Vec<_> = state.forest.values().map(|tree| (tree.cell_index, tree.size, tree.is_mine, tree.is_dormant)).collect();
+    trees.sort_unstable();
+
+    let mut hasher = DefaultHasher::new();
+    (state.day, state.nutrients, state.sun, state.score, state.waiting).hash(&mut hasher);
+    trees.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::mapgen::build_board;
+    use crate::rules::legal_actions;
+    use crate::test_util::position;
+
+    fn search(state: &State) -> Option<(Action, i32)> {
+        let board = build_board(&[]);
+        let actions = legal_actions(state, &board, 0);
+        EndgameSearch::new(EndgameConfig::default()).search(state, &board, &actions, &Deadline::after(Duration::from_secs(10)))
+    }
+
+    #[test]
+    fn completes_on_the_last_day() {
+        // 10 nutrients and 4 for richness 3, better than 4 / 3 sun and a tree
+        let state = position(23).nutrients(10).sun([4, 0]).trees(&[(0, 3, true)]).state();
+        assert_eq!(search(&state), Some((Action::Complete(0), 14)));
+    }
+
+    #[test]
+    fn grows_on_day_22_to_complete_on_day_23() {
+        // 8 - 7 sun left, and 3 gathered by the grown tree on day 23
+        let state = position(22).nutrients(10).sun([8, 0]).trees(&[(0, 2, true)]).state();
+        assert_eq!(search(&state), Some((Action::Grow(0), 14)));
+    }
+
+    #[test]
+    fn reports_negative_margins_rounded() {
+        // The opponent scores 14 against our one extra tree
+        let state = position(23).nutrients(10).sun([0, 4]).trees(&[(0, 3, false), (19, 1, true)]).dormant(&[19]).state();
+        assert_eq!(search(&state), Some((Action::Wait, -14)));
+        assert_eq!(score_margin(-1399), -14);
+        assert_eq!(score_margin(1399), 14);
+        assert_eq!(score_margin(-5), 0);
+        assert_eq!(score_margin(295), 3);
+    }
+
+    #[test]
+    fn finishes_within_an_iteration_budget() {
+        let board = build_board(&[]);
+        let state = position(21)
+            .nutrients(12)
+            .sun([9, 9])
+            .trees(&[(0, 3, true), (1, 2, true), (8, 1, true), (4, 3, false), (5, 2, false), (14, 1, false)])
+            .state();
+        let actions = legal_actions(&state, &board, 0);
+        // What the bot gives the search out of a budget of 300 iterations
+        let deadline = Deadline::after(Duration::from_secs(10)).with_max_iterations(Some(300)).share(0, 0.5);
+        let mut search = EndgameSearch::new(EndgameConfig::default());
+        assert!(search.search(&state, &board, &actions, &deadline).is_some());
+        // Past the first check of the deadline
+        assert!(search.nodes > 1024);
+    }
+}
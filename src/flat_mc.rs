@@ -0,0 +1,97 @@
+//! Flat Monte Carlo: candidate first actions are evaluated with random
+//! rollouts and the final move is picked from the per action statistics.
+//!
+//! Rollouts are allocated by successive halving: the time is split in
+//! log2(candidates) rounds, each spread evenly over the remaining candidates,
+//! and the worse half by mean is dropped after every round.
+
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use crate::config::{flag, parsed_setting};
+use crate::game::{Action, Area};
+use crate::rollout::{choose_rollout_action, root_actions, PlayoutConfig, Rollout};
+use crate::rules::State;
+use crate::stats::{print_statistics, ActionStats, FinalMovePolicy};
+use crate::time::Deadline;
+
+#[derive(Copy, Clone)]
+pub struct FlatMcConfig {
+    pub playout: PlayoutConfig,
+    pub final_move: FinalMovePolicy,
+    // dumps the statistics table to stderr every turn
+    pub debug: bool,
+}
+
+impl Default for FlatMcConfig {
+    fn default() -> Self {
+        FlatMcConfig { playout: PlayoutConfig::default(), final_move: FinalMovePolicy::LowerConfidenceBound, debug: false }
+    }
+}
+
+impl FlatMcConfig {
+    // Defaults overridden by the `final-move` and `debug` settings
+    pub fn from_settings() -> FlatMcConfig {
+        FlatMcConfig {
+            playout: PlayoutConfig::from_settings(),
+            final_move: parsed_setting("final-move").unwrap_or(FlatMcConfig::default().final_move),
+            debug: flag("debug"),
+        }
+    }
+}
+
+pub fn playout_moves<R: Rng>(root: &State, board: &Area, expected_actions: &[Action], config: &FlatMcConfig, deadline: &Deadline, rng: &mut R) -> (Action, f64, i32, i32) {
+    let possible_choices = root_actions(root, board, expected_actions, &config.playout.weights);
+    let playout = &config.playout;
+
+    // keyed by Action::code
+    let mut results: HashMap<u16, ActionStats> = HashMap::new();
+
+    let mut number_of_rolls = 0;
+
+    let mut candidates = possible_choices.clone();
+    let mut rounds_left = (candidates.len() as f64).log2().ceil().max(1.0) as i32;
+
+    while !deadline.reached(number_of_rolls) {
+        let round_deadline = deadline.share(number_of_rolls, 1.0 / rounds_left as f64);
+
+        let mut i = 0;
+        while !round_deadline.reached(number_of_rolls) {
+            let mut rollout = Rollout::new(root.clone());
+
+            // Round robin over our first action, the opponent answers without knowing it
+            let first_action = candidates[i % candidates.len()];
+            let opponent_action = choose_rollout_action(&rollout.state, board, 1, playout, rng);
+            rollout.step(board, &playout.weights, [first_action, opponent_action]);
+
+            rollout.play_out(board, playout, rng, playout.last_day(root.day));
+
+            results.entry(first_action.code()).or_default().add(rollout.points[0] as f64);
+
+            number_of_rolls += 1;
+            i += 1;
+        }
+
+        if rounds_left > 1 {
+            // Candidates the round had no time for are kept
+            let mean = |action: &Action| results.get(&action.code()).map_or(f64::INFINITY, |stats| stats.mean);
+            candidates.sort_by(|a, b| mean(b).partial_cmp(&mean(a)).unwrap());
+            candidates.truncate((candidates.len() + 1) / 2);
+            rounds_left -= 1;
+        }
+    }
+
+    if config.debug {
+        print_statistics(results.iter().map(|(code, stats)| (Action::from_code(*code), *stats)).collect());
+    }
+
+    let value = |action: &Action| results.get(&action.code()).map_or(f64::MIN, |stats| stats.value(config.final_move));
+    let best_action = *candidates
+        .iter()
+        .max_by(|a, b| value(a).partial_cmp(&value(b)).unwrap())
+        .unwrap();
+    let mean = results.get(&best_action.code()).map_or(0.0, |stats| stats.mean);
+
+    (best_action, mean, possible_choices.len() as i32, number_of_rolls)
+}
@@ -0,0 +1,268 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::BuildHasherDefault;
+use std::io;
+use std::io::BufRead;
+use std::str::FromStr;
+
+macro_rules! parse_input {
+    ($x:expr, $t:ident) => ($x.trim().parse::<$t>().unwrap())
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct Tree {
+    pub cell_index: i32,
+    pub size: i32,
+    pub is_mine: bool,
+    pub is_dormant: bool,
+}
+
+// Hashing with fixed keys keeps iteration order, and with it seeded searches,
+// reproducible
+pub type FixedState = BuildHasherDefault<DefaultHasher>;
+
+pub type Forest = HashMap<i32, Tree, FixedState>;
+
+pub fn get_forest() -> Forest {
+    read_forest(&mut io::stdin().lock())
+}
+
+pub fn read_forest<R: BufRead>(input: &mut R) -> Forest {
+    let mut forest = Forest::default();
+
+    let mut input_line = String::new();
+    input.read_line(&mut input_line).unwrap();
+    let number_of_trees = parse_input!(input_line, i32); // the current amount of trees
+    for _ in 0..number_of_trees as usize {
+        let mut input_line = String::new();
+        input.read_line(&mut input_line).unwrap();
+        let inputs = input_line.split(' ').collect::<Vec<_>>();
+        let cell_index = parse_input!(inputs[0], i32); // location of this tree
+        let size = parse_input!(inputs[1], i32); // size of this tree: 0-3
+        let is_mine = parse_input!(inputs[2], i32) == 1; // 1 if this is your tree
+        let is_dormant = parse_input!(inputs[3], i32) == 1; // 1 if this tree is dormant
+        forest.insert(cell_index, Tree { cell_index, size, is_mine, is_dormant });
+    }
+    forest
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Copy, Clone, Debug)]
+pub enum Action {
+    Grow(i32),
+    Seed(i32, i32),
+    Complete(i32),
+    Wait,
+    Null,
+}
+
+// Compact encoding used as key in the searches: the verb in the top bits,
+// then the origin and target cells in 6 bits each.
+const VERB_SHIFT: u16 = 12;
+const CELL_BITS: u16 = 6;
+const CELL_MASK: u16 = (1 << CELL_BITS) - 1;
+
+impl Action {
+    pub fn code(&self) -> u16 {
+        match *self {
+            Action::Wait => 0,
+            Action::Grow(cell) => 1 << VERB_SHIFT | cell as u16,
+            Action::Complete(cell) => 2 << VERB_SHIFT | cell as u16,
+            Action::Seed(target, origin) => 3 << VERB_SHIFT | (origin as u16) << CELL_BITS | target as u16,
+            Action::Null => 4 << VERB_SHIFT,
+        }
+    }
+
+    pub fn from_code(code: u16) -> Action {
+        let target = (code & CELL_MASK) as i32;
+        match code >> VERB_SHIFT {
+            0 => Action::Wait,
+            1 => Action::Grow(target),
+            2 => Action::Complete(target),
+            3 => Action::Seed(target, (code >> CELL_BITS & CELL_MASK) as i32),
+            _ => Action::Null,
+        }
+    }
+}
+
+impl From<&String> for Action {
+    fn from(s: &String) -> Self {
+        s.parse().unwrap_or_else(|e| panic!("Wrong action input: {}", e))
+    }
+}
+
+// An action as printed by a bot, anything after it being its message
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let inputs = s.split_whitespace().collect::<Vec<_>>();
+        let cell = |i: usize| inputs.get(i).and_then(|input| input.parse::<i32>().ok()).ok_or_else(|| format!("Missing cell in {}", s));
+        match inputs.first().copied() {
+            Some("GROW") => Ok(Action::Grow(cell(1)?)),
+            Some("SEED") => Ok(Action::Seed(cell(2)?, cell(1)?)),
+            Some("COMPLETE") => Ok(Action::Complete(cell(1)?)),
+            Some("WAIT") => Ok(Action::Wait),
+            _ => Err(format!("Unknown action {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Grow(i) => write!(f, "GROW {}", i),
+            Action::Seed(target_id, origin_id) => write!(f, "SEED {} {}", origin_id, target_id),
+            Action::Complete(i) => write!(f, "COMPLETE {}", i),
+            Action::Wait => write!(f, "WAIT"),
+            Action::Null => write!(f, "NULL")
+        }
+    }
+}
+
+impl fmt::Display for GameContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "day: {}, sun: {}, nutr: {}, score: {}", self.day, self.sun, self.nutrients, self.score)
+    }
+}
+
+impl fmt::Display for Tree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: mine: {}, dormant: {}, size: {}", self.cell_index, self.is_mine, self.is_dormant, self.size)
+    }
+}
+
+
+pub type ActionList = Vec<Action>;
+
+pub fn get_actionlist() -> ActionList {
+    read_actionlist(&mut io::stdin().lock())
+}
+
+pub fn read_actionlist<R: BufRead>(input: &mut R) -> ActionList {
+    let mut action_list = vec![];
+    let mut input_line = String::new();
+    input.read_line(&mut input_line).unwrap();
+    let number_of_possible_actions = parse_input!(input_line, i32); // all legal actions
+    for _ in 0..number_of_possible_actions as usize {
+        let mut input_line = String::new();
+        input.read_line(&mut input_line).unwrap();
+        let possible_action = input_line.trim_matches('\n').to_string(); // try printing something from here to start with
+        action_list.push(Action::from(&possible_action));
+    }
+    action_list
+}
+
+#[derive(Copy, Clone)]
+pub struct GameContext {
+    pub day: i32,
+    pub nutrients: i32,
+    pub sun: i32,
+    pub score: i32,
+    pub op_sun: i32,
+    pub op_score: i32,
+    pub op_is_waiting: bool,
+}
+
+pub fn get_game_context() -> GameContext {
+    read_game_context(&mut io::stdin().lock())
+}
+
+pub fn read_game_context<R: BufRead>(input: &mut R) -> GameContext {
+    let mut input_line = String::new();
+    input.read_line(&mut input_line).unwrap();
+    let day = parse_input!(input_line, i32); // the game lasts 24 days: 0-23
+    let mut input_line = String::new();
+    input.read_line(&mut input_line).unwrap();
+    let nutrients = parse_input!(input_line, i32); // the base score you gain from the next COMPLETE action
+    let mut input_line = String::new();
+    input.read_line(&mut input_line).unwrap();
+    let inputs = input_line.split(' ').collect::<Vec<_>>();
+    let sun = parse_input!(inputs[0], i32); // your sun points
+    let score = parse_input!(inputs[1], i32); // your current score
+    let mut input_line = String::new();
+    input.read_line(&mut input_line).unwrap();
+    let inputs = input_line.split(' ').collect::<Vec<_>>();
+    let op_sun = parse_input!(inputs[0], i32); // opponent's sun points
+    let op_score = parse_input!(inputs[1], i32); // opponent's score
+    let op_is_waiting = parse_input!(inputs[2], i32) == 1; // whether your opponent is asleep until the next day
+
+    GameContext { day, nutrients, sun, score, op_sun, op_score, op_is_waiting }
+}
+
+#[derive(Clone)]
+pub struct Cell {
+    pub index: i32,
+    pub richness: i32,
+    pub neighbors_ids: Vec<i32>,
+}
+
+pub type Area = HashMap<i32, Cell>;
+
+pub fn get_area() -> Area {
+    read_area(&mut io::stdin().lock())
+}
+
+pub fn read_area<R: BufRead>(input: &mut R) -> Area {
+    let mut area: HashMap<i32, Cell> = HashMap::new();
+
+    let mut input_line = String::new();
+    input.read_line(&mut input_line).unwrap();
+    let number_of_cells = parse_input!(input_line, i32); // 37
+    for _ in 0..number_of_cells as usize {
+        let mut input_line = String::new();
+        let mut neighbors_ids = vec![];
+        input.read_line(&mut input_line).unwrap();
+        let inputs = input_line.split(' ').collect::<Vec<_>>();
+        let index = parse_input!(inputs[0], i32); // 0 is the center cell, the next cells spiral outwards
+        let richness = parse_input!(inputs[1], i32); // 0 if the cell is unusable, 1-3 for usable cells
+        for neighbor in &inputs[2..8] {
+            neighbors_ids.push(parse_input!(neighbor, i32)); // the index of the neighbouring cell for each direction
+        }
+        area.insert(index, Cell { index, richness, neighbors_ids });
+    }
+    area
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_actions() -> Vec<Action> {
+        let mut actions = vec![Action::Wait, Action::Null];
+        for cell in 0..37 {
+            actions.extend([Action::Grow(cell), Action::Complete(cell)]);
+            actions.extend((0..37).filter(|origin| *origin != cell).map(|origin| Action::Seed(cell, origin)));
+        }
+        actions
+    }
+
+    #[test]
+    fn code_round_trip() {
+        let actions = all_actions();
+        for action in &actions {
+            assert_eq!(Action::from_code(action.code()), *action);
+        }
+        let mut codes: Vec<u16> = actions.iter().map(Action::code).collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), actions.len());
+    }
+
+    #[test]
+    fn protocol_round_trip() {
+        for action in all_actions().into_iter().filter(|action| *action != Action::Null) {
+            assert_eq!(action.to_string().parse::<Action>(), Ok(action));
+        }
+    }
+
+    #[test]
+    fn seed_is_written_origin_first() {
+        // SEED <origin> <target>, with Seed(target, origin)
+        assert_eq!("SEED 1 7".parse::<Action>(), Ok(Action::Seed(7, 1)));
+        assert_eq!(Action::Seed(7, 1).to_string(), "SEED 1 7");
+        assert_eq!("GROW 5 message".parse::<Action>(), Ok(Action::Grow(5)));
+        assert!("SEED 1".parse::<Action>().is_err());
+    }
+}
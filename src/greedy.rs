@@ -0,0 +1,117 @@
+//! Rule based bot, the reference baseline for benchmarking the searches. It
+//! is deterministic and takes no time: one pass over the legal actions, in
+//! order of preference
+//!
+//! 1. COMPLETE the best scored big tree when the day and the nutrients left
+//!    make the harvest worth more than the sun the tree would still gather,
+//! 2. GROW the highest scored tree that can still be harvested, the cheapest
+//!    one on ties,
+//! 3. SEED for free on the richest cell out of the line of our trees, so
+//!    that neither shades the other,
+//! 4. WAIT.
+//!
+//! Actions are scored with calculate_game_points_from_action, the same as
+//! in the rollouts, and harvesting starts on the greedy_complete_from_day
+//! weight.
+
+use std::collections::HashMap;
+
+use crate::bot::{Bot, Decision};
+use crate::game::{Action, Area};
+use crate::heuristics::calculate_game_points_from_action;
+use crate::hex::{cube_coordinates, distance, Cube, BOARD_RADIUS};
+use crate::rollout::seeds_allowed;
+use crate::rules::{get_cost_of_action, is_owned_by, legal_actions, richness_bonus, State, LAST_DAY};
+use crate::time::Deadline;
+use crate::weights::EvalWeights;
+
+#[derive(Default)]
+pub struct GreedyBot {
+    board: Area,
+    coordinates: HashMap<i32, Cube>,
+    weights: EvalWeights,
+}
+
+impl GreedyBot {
+    fn points(&self, state: &State, action: &Action) -> i32 {
+        calculate_game_points_from_action(action, state.nutrients, &self.board, &state.forest, state.day, 0, &self.weights)
+    }
+
+    // Harvest value against the sun the tree would still gather, at most 3
+    // a day or one point; every big tree must go when one a day is left for each
+    fn complete_warranted(&self, state: &State, cell_index: i32) -> bool {
+        let days_left = LAST_DAY + 1 - state.day;
+        let harvest = state.nutrients + richness_bonus(self.board[&cell_index].richness);
+        state.trees_counts[0][3] >= days_left || (state.day >= self.weights.greedy_complete_from_day && harvest > days_left)
+    }
+
+    // One growth step a day, then a day to complete
+    fn can_still_harvest(&self, state: &State, cell_index: i32) -> bool {
+        let days_left = LAST_DAY + 1 - state.day;
+        3 - state.forest[&cell_index].size < days_left
+    }
+
+    // Our trees on one of the lines through the cell and close enough for a
+    // tree to shade the other
+    fn own_trees_in_line(&self, state: &State, cell_index: i32) -> usize {
+        let cube = self.coordinates[&cell_index];
+        state
+            .forest
+            .values()
+            .filter(|tree| is_owned_by(tree, 0))
+            .map(|tree| self.coordinates[&tree.cell_index])
+            .filter(|other| (other.0 == cube.0 || other.1 == cube.1 || other.2 == cube.2) && distance(*other, cube) <= BOARD_RADIUS)
+            .count()
+    }
+
+    fn choose(&self, state: &State, actions: &[Action]) -> Action {
+        let mut candidates = legal_actions(state, &self.board, 0);
+        candidates.retain(|action| actions.contains(action));
+        // Sorted so that ties always go the same way
+        candidates.sort();
+
+        let best_complete = candidates
+            .iter()
+            .filter(|action| matches!(action, Action::Complete(cell_index) if self.complete_warranted(state, *cell_index)))
+            .max_by_key(|action| self.points(state, action));
+        if let Some(action) = best_complete {
+            return *action;
+        }
+
+        let best_grow = candidates
+            .iter()
+            .filter(|action| matches!(action, Action::Grow(cell_index) if self.can_still_harvest(state, *cell_index)))
+            .max_by_key(|action| (self.points(state, action), -get_cost_of_action(action, &state.forest, state.trees_counts[0])));
+        if let Some(action) = best_grow {
+            return *action;
+        }
+
+        if state.trees_counts[0][0] == 0 && seeds_allowed(state, 0, &self.weights) {
+            let best_seed = candidates
+                .iter()
+                .filter_map(|action| match action {
+                    Action::Seed(target, _) => Some((action, *target)),
+                    _ => None,
+                })
+                .max_by_key(|(action, target)| (-(self.own_trees_in_line(state, *target) as i32), self.board[target].richness, self.points(state, action)));
+            if let Some((action, _)) = best_seed {
+                return *action;
+            }
+        }
+
+        Action::Wait
+    }
+}
+
+impl Bot for GreedyBot {
+    fn init(&mut self, board: &Area) {
+        self.board = board.clone();
+        self.coordinates = cube_coordinates(board);
+        self.weights = EvalWeights::from_settings();
+    }
+
+    fn decide(&mut self, state: &State, actions: &[Action], _deadline: Deadline) -> Decision {
+        let action = self.choose(state, actions);
+        Decision::new(action, format!("points: {}", self.points(state, &action)))
+    }
+}
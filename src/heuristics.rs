@@ -0,0 +1,92 @@
+//! Hand tuned scoring of single actions, used to shape rollout results, and
+//! of positions where a rollout is cut short. The weights are in EvalWeights.
+
+use crate::game::{Action, Area, Forest};
+use crate::rules::{is_owned_by, richness_bonus, State, LAST_DAY};
+use crate::weights::EvalWeights;
+
+// Sun still needed to grow a tree of each size and complete it
+const SUN_TO_HARVEST: [i32; 4] = [1 + 3 + 7 + 4, 3 + 7 + 4, 7 + 4, 4];
+
+pub fn calculate_game_points_from_action(action: &Action, nutrients: i32, board: &Area, trees: &Forest, day: i32, player: usize, weights: &EvalWeights) -> i32 {
+    match action {
+        Action::Complete(cell_index) => {
+            let mut shadow_cost = -calc_shadow_points(*cell_index, trees, board, day + 1, false, player)
+                - calc_shadow_points(*cell_index, trees, board, day + 2, false, player);
+            if day > weights.harvest_bonus_after_day {
+                shadow_cost += nutrients + richness_bonus(board[cell_index].richness);
+            }
+            shadow_cost + if day >= weights.complete_late_day { weights.complete_late_bonus } else { 0 }
+        }
+        Action::Grow(target_id) => calc_shadow_points(*target_id, trees, board, day + 1, true, player)
+            + calc_shadow_points(*target_id, trees, board, day + 2, true, player)
+            + if trees[target_id].size == 0 { weights.grow_seed_bonus } else { 0 }
+            + if day >= weights.grow_late_day { weights.grow_late_base + trees[target_id].size } else { 0 },
+
+        Action::Seed(target_id, _) => {
+            let n = board[target_id].neighbors_ids.iter().filter(|id| !trees.contains_key(id)).count() as i32;
+            -n * weights.seed_empty_neighbor_penalty
+                + (36 - target_id) / weights.seed_center_divisor
+                + board[target_id].richness * weights.seed_richness
+        }
+        _ => 0
+    }
+}
+
+// Shadow cast by the tree on `target_id` on the given day, counted positive
+// on the opponent's trees and negative on `player`'s own.
+pub fn calc_shadow_points(target_id: i32, trees: &Forest, board: &Area, day: i32, action_grow: bool, player: usize) -> i32 {
+    let tree_size = trees[&target_id].size;
+    let shadow_direction = day % 6;
+
+    let mut current_id = target_id;
+
+    let mut points = 0;
+
+    for i in 0..tree_size + 1 {
+        current_id = board[&current_id].neighbors_ids[shadow_direction as usize];
+        if current_id == -1 {
+            break;
+        }
+        if !action_grow || i == tree_size {
+            if let Some(tree) = trees.get(&current_id) {
+                points += if is_owned_by(tree, player) {
+                    -tree.size
+                } else {
+                    tree.size
+                }
+            }
+        }
+    }
+
+    points
+}
+
+// Worth in points of what `player` still has when a rollout stops before the
+// end of the game: trees that can still be harvested, scaled by how far they
+// are from it, the projected sun income and the sun in the bank.
+pub fn evaluate_leaf(state: &State, board: &Area, player: usize, weights: &EvalWeights) -> i32 {
+    let days_left = LAST_DAY + 1 - state.day;
+
+    // Nutrients drop with every harvest, assume half the big trees go first
+    let big_trees = state.trees_counts[0][3] + state.trees_counts[1][3];
+    let nutrients = (state.nutrients - big_trees / 2).max(0) as f64;
+
+    let mut value = 0.0;
+    let mut sun_income = 0;
+    for tree in state.forest.values().filter(|tree| is_owned_by(tree, player)) {
+        sun_income += tree.size;
+
+        // one day per growth step, then one to complete
+        if 4 - tree.size > days_left {
+            continue;
+        }
+        let harvest = nutrients + richness_bonus(board[&tree.cell_index].richness) as f64;
+        let cost = SUN_TO_HARVEST[tree.size as usize] as f64 / 3.0;
+        value += (harvest - cost).max(0.0) * weights.tree_maturity[tree.size as usize];
+    }
+
+    value += (sun_income * days_left) as f64 * weights.sun_income_share / 3.0;
+    value += state.sun[player] as f64 / 3.0;
+    value.round() as i32
+}
@@ -0,0 +1,60 @@
+//! Cube coordinates of the hexagonal board.
+
+use std::collections::HashMap;
+
+use crate::game::Area;
+
+pub type Cube = (i32, i32, i32);
+
+// Offsets of the neighbours in the direction order of the input, which is
+// also the order the shadows turn in: direction `day % 6` on a given day
+pub const DIRECTIONS: [Cube; 6] = [(1, -1, 0), (1, 0, -1), (0, 1, -1), (-1, 1, 0), (-1, 0, 1), (0, -1, 1)];
+
+pub const BOARD_RADIUS: i32 = 3;
+
+pub fn neighbor(cube: Cube, direction: usize) -> Cube {
+    let offset = DIRECTIONS[direction];
+    (cube.0 + offset.0, cube.1 + offset.1, cube.2 + offset.2)
+}
+
+pub fn distance(a: Cube, b: Cube) -> i32 {
+    ((a.0 - b.0).abs() + (a.1 - b.1).abs() + (a.2 - b.2).abs()) / 2
+}
+
+// Coordinates in cell index order: the center, then rings spiralling
+// outwards starting in direction 0
+pub fn spiral_coordinates() -> Vec<Cube> {
+    let mut coordinates = vec![(0, 0, 0)];
+    for radius in 1..=BOARD_RADIUS {
+        let mut cube = (0, 0, 0);
+        for _ in 0..radius {
+            cube = neighbor(cube, 0);
+        }
+        for side in 0..6 {
+            for _ in 0..radius {
+                coordinates.push(cube);
+                cube = neighbor(cube, (side + 2) % 6);
+            }
+        }
+    }
+    coordinates
+}
+
+// Coordinates of every cell of a board, found by walking the neighbours from
+// the center cell
+pub fn cube_coordinates(board: &Area) -> HashMap<i32, Cube> {
+    let mut coordinates = HashMap::new();
+    coordinates.insert(0, (0, 0, 0));
+    let mut to_visit = vec![0];
+
+    while let Some(index) = to_visit.pop() {
+        let cube = coordinates[&index];
+        for (direction, neighbor_id) in board[&index].neighbors_ids.iter().enumerate() {
+            if *neighbor_id != -1 && !coordinates.contains_key(neighbor_id) {
+                coordinates.insert(*neighbor_id, neighbor(cube, direction));
+                to_visit.push(*neighbor_id);
+            }
+        }
+    }
+    coordinates
+}
@@ -0,0 +1,30 @@
+pub mod config;
+pub mod game;
+pub mod rules;
+pub mod heuristics;
+pub mod rollout;
+pub mod flat_mc;
+pub mod stats;
+pub mod uct;
+pub mod time;
+pub mod rng;
+pub mod day_plan;
+pub mod endgame;
+pub mod hex;
+pub mod mapgen;
+pub mod opening_book;
+pub mod opening_book_data;
+pub mod tracker;
+pub mod opponent_model;
+pub mod bot;
+pub mod greedy;
+pub mod weights;
+pub mod weights_data;
+pub mod selfplay;
+pub mod protocol;
+pub mod render;
+pub mod transcript;
+pub mod svg;
+
+#[cfg(test)]
+mod test_util;
@@ -0,0 +1,103 @@
+//! Random maps and starting positions following the referee's generation
+//! rules, for local games.
+
+use std::collections::HashMap;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::game::{Area, Cell, Forest, Tree};
+use crate::hex::{neighbor, spiral_coordinates};
+use crate::rules::{get_my_sun_points, State};
+
+const MAX_UNUSABLE_CELLS: usize = 10;
+const STARTING_NUTRIENTS: i32 = 20;
+
+// Richness by ring: the center and first ring are rich, the outer ring poor
+fn ring_richness(index: usize) -> i32 {
+    match index {
+        0..=6 => 3,
+        7..=18 => 2,
+        _ => 1,
+    }
+}
+
+// Index of the cell mirrored through the center
+pub fn opposite_cell(index: i32) -> i32 {
+    if index == 0 {
+        return 0;
+    }
+    let ring_start = match index {
+        1..=6 => 1,
+        7..=18 => 7,
+        _ => 19,
+    };
+    let ring_size = match ring_start {
+        1 => 6,
+        7 => 12,
+        _ => 18,
+    };
+    ring_start + (index - ring_start + ring_size / 2) % ring_size
+}
+
+pub fn generate_board<R: Rng>(rng: &mut R) -> Area {
+    // Unusable cells come in symmetric pairs
+    let mut unusable = vec![];
+    let pairs = rng.gen_range(0..=MAX_UNUSABLE_CELLS / 2);
+    let mut candidates: Vec<i32> = (1..spiral_coordinates().len() as i32).filter(|i| *i < opposite_cell(*i)).collect();
+    candidates.shuffle(rng);
+    for index in candidates.into_iter().take(pairs) {
+        unusable.push(index);
+        unusable.push(opposite_cell(index));
+    }
+
+    build_board(&unusable)
+}
+
+// The board with its ring richness, the `unusable` cells at richness 0
+pub fn build_board(unusable: &[i32]) -> Area {
+    let coordinates = spiral_coordinates();
+    let index_of: HashMap<_, _> = coordinates.iter().enumerate().map(|(i, cube)| (*cube, i as i32)).collect();
+
+    coordinates
+        .iter()
+        .enumerate()
+        .map(|(i, cube)| {
+            let index = i as i32;
+            let richness = if unusable.contains(&index) { 0 } else { ring_richness(i) };
+            let neighbors_ids = (0..6).map(|direction| *index_of.get(&neighbor(*cube, direction)).unwrap_or(&-1)).collect();
+            (index, Cell { index, richness, neighbors_ids })
+        })
+        .collect()
+}
+
+// Two small trees each on the outer ring, the opponent's mirroring ours
+pub fn initial_state<R: Rng>(board: &Area, rng: &mut R) -> State {
+    let mut outer_ring: Vec<i32> = (19..37).filter(|i| board[i].richness > 0).collect();
+    outer_ring.shuffle(rng);
+
+    let mut forest = Forest::default();
+    for cell_index in outer_ring {
+        let opposite = opposite_cell(cell_index);
+        if forest.contains_key(&cell_index) || forest.contains_key(&opposite) {
+            continue;
+        }
+        forest.insert(cell_index, Tree { cell_index, size: 1, is_mine: true, is_dormant: false });
+        forest.insert(opposite, Tree { cell_index: opposite, size: 1, is_mine: false, is_dormant: false });
+        if forest.len() == 4 {
+            break;
+        }
+    }
+
+    // The first day's sun is gathered before the first turn
+    let sun = [get_my_sun_points(board, &0, &forest, 0), get_my_sun_points(board, &0, &forest, 1)];
+    State {
+        day: 0,
+        nutrients: STARTING_NUTRIENTS,
+        sun,
+        score: [0, 0],
+        waiting: [false, false],
+        forest,
+        trees_counts: [[0, 2, 0, 0], [0, 2, 0, 0]],
+    }
+}
@@ -0,0 +1,276 @@
+//! Opening book: best actions for the first days, found by long offline
+//! searches (see the `book` binary) and embedded in the submitted file.
+//!
+//! Maps are random, so positions are keyed by a reduced set of features: the
+//! day, nutrients, sun, score and trees, seen from the side to play.
+//! Richness is left out, it only differs between maps by the unusable cells
+//! and a book move seeding on one is not legal anyway. The key is also
+//! normalized over the 12 rotations and mirrors of the board, and the book
+//! stores the move in the normalized cells. These are only approximate
+//! symmetries, as the sun turns with the days, but in the first days the
+//! trees are too small for the shadows to decide much.
+//!
+//! The book is stored as base64 of 10 byte records: the 64 bit position key
+//! and the 16 bit Action::code, both little endian, next to the shortest
+//! think time its moves were searched with.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::game::Action;
+use crate::hex::spiral_coordinates;
+use crate::opening_book_data::{BOOK, THINK_MS};
+use crate::rules::State;
+
+const RECORD_SIZE: usize = 10;
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub struct OpeningBook {
+    entries: HashMap<u64, u16>,
+    // shortest search behind a move of the book, a live search at least as
+    // long being expected to do as well
+    pub think: Duration,
+    // cell permutations of the rotations and mirrors of the board
+    symmetries: Vec<Vec<i32>>,
+}
+
+impl Default for OpeningBook {
+    fn default() -> Self {
+        OpeningBook { entries: HashMap::new(), think: Duration::ZERO, symmetries: symmetries() }
+    }
+}
+
+impl OpeningBook {
+    pub fn embedded() -> OpeningBook {
+        OpeningBook { think: Duration::from_millis(THINK_MS), ..OpeningBook::decode(BOOK) }
+    }
+
+    // Whether the book is worth asking with this much time to search instead
+    pub fn applies(&self, budget: Duration) -> bool {
+        budget < self.think
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // The book's action for the position if it is still legal
+    pub fn lookup(&self, state: &State, expected_actions: &[Action]) -> Option<Action> {
+        let (key, symmetry) = self.normalized_key(state);
+        let inverse = inverse(&self.symmetries[symmetry]);
+        self.entries
+            .get(&key)
+            .map(|code| map_action(Action::from_code(*code), &inverse))
+            .filter(|action| expected_actions.contains(action))
+    }
+
+    pub fn insert(&mut self, state: &State, action: Action) {
+        let (key, symmetry) = self.normalized_key(state);
+        self.entries.insert(key, map_action(action, &self.symmetries[symmetry]).code());
+    }
+
+    // Smallest key over the symmetries of the board, and the symmetry giving it
+    fn normalized_key(&self, state: &State) -> (u64, usize) {
+        self.symmetries.iter().enumerate().map(|(i, cells)| (position_key(state, cells), i)).min().unwrap()
+    }
+
+    pub fn encode(&self) -> String {
+        // Sorted so that regenerating the same book gives the same file
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        entries.sort();
+
+        let mut bytes = Vec::with_capacity(entries.len() * RECORD_SIZE);
+        for (key, code) in entries {
+            bytes.extend_from_slice(&key.to_le_bytes());
+            bytes.extend_from_slice(&code.to_le_bytes());
+        }
+        encode_base64(&bytes)
+    }
+
+    pub fn decode(encoded: &str) -> OpeningBook {
+        let entries = decode_base64(encoded)
+            .chunks_exact(RECORD_SIZE)
+            .map(|record| {
+                let mut key = [0; 8];
+                key.copy_from_slice(&record[..8]);
+                (u64::from_le_bytes(key), u16::from_le_bytes([record[8], record[9]]))
+            })
+            .collect();
+        OpeningBook { entries, ..OpeningBook::default() }
+    }
+}
+
+// Cell index permutations of the 6 rotations, each with and without a mirror
+fn symmetries() -> Vec<Vec<i32>> {
+    let coordinates = spiral_coordinates();
+    let index_of: HashMap<_, _> = coordinates.iter().enumerate().map(|(i, cube)| (*cube, i as i32)).collect();
+
+    let mut symmetries = vec![];
+    for mirrored in [false, true] {
+        for rotation in 0..6 {
+            let cells = coordinates
+                .iter()
+                .map(|&cube| {
+                    let (mut x, mut y, mut z) = if mirrored { (cube.0, cube.2, cube.1) } else { cube };
+                    for _ in 0..rotation {
+                        let rotated = (-z, -x, -y);
+                        x = rotated.0;
+                        y = rotated.1;
+                        z = rotated.2;
+                    }
+                    index_of[&(x, y, z)]
+                })
+                .collect();
+            symmetries.push(cells);
+        }
+    }
+    symmetries
+}
+
+fn inverse(cells: &[i32]) -> Vec<i32> {
+    let mut inverse = vec![0; cells.len()];
+    for (index, cell) in cells.iter().enumerate() {
+        inverse[*cell as usize] = index as i32;
+    }
+    inverse
+}
+
+fn map_action(action: Action, cells: &[i32]) -> Action {
+    let cell = |index: i32| cells[index as usize];
+    match action {
+        Action::Grow(index) => Action::Grow(cell(index)),
+        Action::Complete(index) => Action::Complete(cell(index)),
+        Action::Seed(target, origin) => Action::Seed(cell(target), cell(origin)),
+        Action::Wait | Action::Null => action,
+    }
+}
+
+// FNV-1a over the features of the position with the cells permuted by
+// `cells`, stable across compilers unlike the std hashers
+fn position_key(state: &State, cells: &[i32]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut add = |value: i32| {
+        for byte in value.to_le_bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    };
+
+    add(state.day);
+    add(state.nutrients);
+    for player in 0..2 {
+        add(state.sun[player]);
+        add(state.score[player]);
+        add(state.waiting[player] as i32);
+    }
+    let mut trees = vec![0; cells.len()];
+    for tree in state.forest.values() {
+        trees[cells[tree.cell_index as usize] as usize] = 1 + tree.size + 4 * tree.is_mine as i32 + 8 * tree.is_dormant as i32;
+    }
+    for tree in trees {
+        add(tree);
+    }
+    hash
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16 | (*chunk.get(1).unwrap_or(&0) as u32) << 8 | *chunk.get(2).unwrap_or(&0) as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+fn decode_base64(encoded: &str) -> Vec<u8> {
+    let values: Vec<u32> = encoded
+        .bytes()
+        .filter_map(|c| BASE64.iter().position(|b| *b == c).map(|v| v as u32))
+        .collect();
+
+    let mut bytes = Vec::with_capacity(values.len() * 3 / 4);
+    for chunk in values.chunks(4) {
+        let n = chunk.iter().enumerate().fold(0, |n, (i, v)| n | v << (18 - 6 * i));
+        for i in 0..chunk.len() - 1 {
+            bytes.push((n >> (16 - 8 * i)) as u8);
+        }
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::distance;
+    use crate::test_util::position;
+
+    // Day 1 position with the given trees, as (cell, size, is_mine)
+    fn state_with(trees: &[(i32, i32, bool)]) -> State {
+        position(1).sun([3, 2]).trees(trees).state()
+    }
+
+    #[test]
+    fn base64_round_trip() {
+        for length in 0..12 {
+            let bytes: Vec<u8> = (0..length).map(|i| (i * 37 + 200) as u8).collect();
+            assert_eq!(decode_base64(&encode_base64(&bytes)), bytes);
+        }
+        assert_eq!(encode_base64(b"book"), "Ym9vaw==");
+    }
+
+    #[test]
+    fn book_round_trip() {
+        let mut book = OpeningBook::default();
+        book.insert(&state_with(&[(20, 1, true), (29, 1, false)]), Action::Grow(20));
+        book.insert(&state_with(&[(22, 1, true), (31, 1, false)]), Action::Seed(10, 22));
+
+        let decoded = OpeningBook::decode(&book.encode());
+        assert_eq!(decoded.entries, book.entries);
+    }
+
+    #[test]
+    fn lookup_in_a_rotated_or_mirrored_position() {
+        let trees = [(20, 1, true), (22, 1, true), (29, 1, false), (31, 1, false)];
+        let mut book = OpeningBook::default();
+        book.insert(&state_with(&trees), Action::Seed(9, 22));
+
+        for cells in symmetries() {
+            let moved: Vec<_> = trees.iter().map(|&(cell, size, is_mine)| (cells[cell as usize], size, is_mine)).collect();
+            let action = Action::Seed(cells[9], cells[22]);
+            assert_eq!(book.lookup(&state_with(&moved), &[Action::Wait, action]), Some(action));
+        }
+    }
+
+    #[test]
+    fn consulted_with_less_time_than_its_searches() {
+        let book = OpeningBook::embedded();
+        assert!(!book.is_empty());
+        assert!(book.applies(Duration::from_millis(995)));
+        assert!(!book.applies(book.think));
+    }
+
+    #[test]
+    fn symmetries_keep_neighbours() {
+        let coordinates = spiral_coordinates();
+        let symmetries = symmetries();
+        assert_eq!(symmetries.len(), 12);
+        for cells in &symmetries {
+            for a in 0..coordinates.len() {
+                for b in 0..coordinates.len() {
+                    let moved = distance(coordinates[cells[a] as usize], coordinates[cells[b] as usize]);
+                    assert_eq!(moved, distance(coordinates[a], coordinates[b]));
+                }
+            }
+        }
+    }
+}
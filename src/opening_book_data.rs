@@ -0,0 +1,3 @@
+// Generated by `cargo run --release --bin book`, do not edit.
+pub const THINK_MS: u64 = 500;
+pub const BOOK: &str = "Mc7SRK5eBQAAAFQT1OXnGpIAAADwxquaRTq7ACEQ8PpzpQha7wDKNQAfnTLiiBwBAAD0oEqyfkuIAQAAMP6FnKd8swIAAPCLn5rT3+cCAADQSR5hAIb5AgAAw9JTYLtsGwMAAHBjHMJg5SsDAAB2Ola6hxdfAx4QgdG8y1vApAMAAHRfHYyLKsUDAAC0c2SgeebRAwAA0N+EfT9jPAQLNvBTcxIDuvsEAADwuQou1LkLBSEQq4Dek6PkLAUAAEE19fWTUGQGAADwnma9oyd1BgAAcMIGuS2JvwYAAPCLvHjyUe4GWDZUQT2/sYL+BgAAsHONwRrlGgcAADCFFJx9qCoIEDiQXbPyReU6CI439jb2i3VhQAkUEHBwJvaFGGgJFhDACa9XH0kLCiQQlHOHwQM2HwoAABDyXssEFmwKAADwwdWOvfx0Cg03cLlGDABTqwoAADAjGylk4csKHxAbrxHrjynwCgAAFPXfCoE3/goAAOS5gCOXa/ALAABBrtnN2eETDQAAxPLZDqaypg0AAEHqz08iOhUOAABwEtsXqgd9DuQ0wdD/MDYFPBAAAFCRtjAQ258QAAB0cndoNDfMEAAApBULzz0onBEAAIH+l/wMv+YSAACW8arp8Z1GEwAAALHSPSSqDxQAAIRlcHORRIYUAACQkcLGQVrsFAAAcKc+sf6vrhUYEPCkBHztZggWFRDUdYmtnSbhFgAAFCIgEOan9xYAAEF6eox24+gYAABwtjlQvM9DGhoQAS1vv1SsTBoAALCwxsPXlVwgAADQfiH1X8m1IAAA49mTMhIi9SAAAPBvYGedezAijzfBrlgS3jGJJQAAQToIovWIZScAAOTy/dz1lZwnAACwVq6qZvOZKSQQQcCN5qyaxCkAAPA9wig/RxstAABw228vfj3LLRcQMItIrNsVhTEaELDdb03tb6kxGRDwbBc+Fh9tMtY18LamAIXYyjOLNpYp294IWTw3AACBYAJBV74aOQAAAZWcQszgBUEAAIHnw+PdOipBAACwCsoXScHbUgAAMAg5m6cbtocXEA==";
@@ -0,0 +1,142 @@
+//! Statistics of the opponent's choices over the current match, from the
+//! inferred actions history, turned into a bias of their playout policy.
+//!
+//! Counts are smoothed with a prior worth a few turns, so the bias stays
+//! neutral until the opponent has shown a clear style: seeding a lot or
+//! little, harvesting early or late, favouring rich or poor cells. Keeping
+//! a tree they could have completed counts as harvesting late.
+
+use crate::game::{Action, Area};
+use crate::tracker::ObservedTurn;
+
+// Seeds per turn of an average opponent and how many turns that guess weighs
+const PRIOR_SEED_RATE: f64 = 0.25;
+const PRIOR_TURNS: f64 = 8.0;
+// Observations per richness assumed before any is made
+const PRIOR_RICHNESS: f64 = 3.0;
+// Weight of a COMPLETE before the day the opponent usually starts harvesting
+const EARLY_COMPLETE_FACTOR: f64 = 0.3;
+const MIN_FACTOR: f64 = 0.25;
+const MAX_FACTOR: f64 = 4.0;
+
+// Multipliers for the playout weights of the opponent's actions
+#[derive(Copy, Clone, Debug)]
+pub struct OpponentBias {
+    pub seed: f64,
+    // first day the opponent completed a tree or, until they do, the day
+    // after the last one they could have and did not, 0 when not seen yet
+    pub complete_from_day: i32,
+    pub richness: [f64; 4],
+}
+
+impl OpponentBias {
+    pub fn factor(&self, action: &Action, day: i32, board: &Area) -> f64 {
+        match action {
+            Action::Seed(target, _) => self.seed * self.richness[board[target].richness as usize],
+            Action::Grow(cell) => self.richness[board[cell].richness as usize],
+            Action::Complete(_) if day < self.complete_from_day => EARLY_COMPLETE_FACTOR,
+            _ => 1.0,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct OpponentModel {
+    turns: i32,
+    seeds: i32,
+    first_complete_day: Option<i32>,
+    // last day they had a tree to complete and kept it, before any COMPLETE
+    last_declined_day: Option<i32>,
+    // richness of the cells they seeded or grew on
+    richness_counts: [i32; 4],
+}
+
+impl OpponentModel {
+    pub fn update(&mut self, observed: &ObservedTurn, board: &Area) {
+        self.turns += 1;
+        for action in &observed.actions {
+            match action {
+                Action::Seed(target, _) => {
+                    self.seeds += 1;
+                    self.richness_counts[board[target].richness as usize] += 1;
+                }
+                Action::Grow(cell) => self.richness_counts[board[cell].richness as usize] += 1,
+                Action::Complete(_) if self.first_complete_day.is_none() => self.first_complete_day = Some(observed.day),
+                _ => (),
+            }
+        }
+        if observed.could_complete && self.first_complete_day.is_none() {
+            self.last_declined_day = Some(observed.day);
+        }
+    }
+
+    pub fn bias(&self) -> OpponentBias {
+        let seed_rate = (self.seeds as f64 + PRIOR_SEED_RATE * PRIOR_TURNS) / (self.turns as f64 + PRIOR_TURNS);
+
+        let observed: i32 = self.richness_counts[1..].iter().sum();
+        let mean = observed as f64 / 3.0 + PRIOR_RICHNESS;
+        let mut richness = [1.0; 4];
+        for (factor, count) in richness.iter_mut().zip(self.richness_counts).skip(1) {
+            *factor = ((count as f64 + PRIOR_RICHNESS) / mean).clamp(MIN_FACTOR, MAX_FACTOR);
+        }
+
+        OpponentBias {
+            seed: (seed_rate / PRIOR_SEED_RATE).clamp(MIN_FACTOR, MAX_FACTOR),
+            complete_from_day: self.first_complete_day.or(self.last_declined_day.map(|day| day + 1)).unwrap_or(0),
+            richness,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapgen::build_board;
+    use crate::test_util::{position, Position};
+    use crate::tracker::OpponentTracker;
+
+    // The model fed with what the tracker infers from consecutive positions,
+    // as the bot does
+    fn model_after(positions: &[Position]) -> OpponentModel {
+        let board = build_board(&[]);
+        let mut tracker = OpponentTracker::default();
+        let mut model = OpponentModel::default();
+        for position in positions {
+            if let Some(observed) = tracker.observe(&position.state(), &board) {
+                model.update(observed, &board);
+            }
+        }
+        model
+    }
+
+    #[test]
+    fn neutral_before_any_tree_to_complete() {
+        let board = build_board(&[]);
+        let days: Vec<_> = (0..6).map(|day| position(day).sun([10, 10]).trees(&[(0, 1, true), (4, 2, false)])).collect();
+
+        let bias = model_after(&days).bias();
+
+        assert_eq!(bias.complete_from_day, 0);
+        assert_eq!(bias.factor(&Action::Complete(4), 6, &board), 1.0);
+    }
+
+    #[test]
+    fn keeping_a_tree_to_complete_is_harvesting_late() {
+        let board = build_board(&[]);
+        // A big tree and the sun to complete it kept from day 8 to 13
+        let mut days: Vec<_> = (8..15).map(|day| position(day).sun([10, 10]).trees(&[(0, 1, true), (4, 3, false), (5, 2, false)])).collect();
+
+        let bias = model_after(&days).bias();
+        assert_eq!(bias.complete_from_day, 14);
+        assert_eq!(bias.factor(&Action::Complete(4), 14, &board), 1.0);
+        assert_eq!(bias.factor(&Action::Complete(4), 13, &board), EARLY_COMPLETE_FACTOR);
+
+        // Completed on day 14, then the next big tree kept until day 17
+        days.push(position(14).nutrients(19).sun([10, 6]).trees(&[(0, 1, true), (5, 2, false)]).dormant(&[0]));
+        days.extend((15..18).map(|day| position(day).nutrients(19).sun([10, 10]).trees(&[(0, 1, true), (5, 3, false)])));
+
+        let bias = model_after(&days).bias();
+        assert_eq!(bias.complete_from_day, 14);
+        assert_eq!(bias.factor(&Action::Complete(5), 14, &board), 1.0);
+    }
+}
@@ -0,0 +1,41 @@
+//! The referee's side of the CodinGame protocol: the input a bot is sent,
+//! in the format the functions of game.rs read.
+
+use std::fmt::Write;
+
+use crate::game::{Action, Area};
+use crate::rules::State;
+
+// Cells in index order, each with its richness and neighbours
+pub fn area_input(board: &Area) -> String {
+    let mut input = format!("{}\n", board.len());
+    for index in 0..board.len() as i32 {
+        let cell = &board[&index];
+        let neighbors: Vec<String> = cell.neighbors_ids.iter().map(|id| id.to_string()).collect();
+        writeln!(input, "{} {} {}", index, cell.richness, neighbors.join(" ")).unwrap();
+    }
+    input
+}
+
+// Input of one turn for the player seeing the game as player 0, trees in
+// cell order
+pub fn turn_input(state: &State, actions: &[Action]) -> String {
+    let mut input = String::new();
+    writeln!(input, "{}", state.day).unwrap();
+    writeln!(input, "{}", state.nutrients).unwrap();
+    writeln!(input, "{} {}", state.sun[0], state.score[0]).unwrap();
+    writeln!(input, "{} {} {}", state.sun[1], state.score[1], state.waiting[1] as i32).unwrap();
+
+    let mut trees: Vec<_> = state.forest.values().collect();
+    trees.sort_by_key(|tree| tree.cell_index);
+    writeln!(input, "{}", trees.len()).unwrap();
+    for tree in trees {
+        writeln!(input, "{} {} {} {}", tree.cell_index, tree.size, tree.is_mine as i32, tree.is_dormant as i32).unwrap();
+    }
+
+    writeln!(input, "{}", actions.len()).unwrap();
+    for action in actions {
+        writeln!(input, "{}", action).unwrap();
+    }
+    input
+}
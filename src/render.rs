@@ -0,0 +1,174 @@
+//! Text rendering of a position: the 37 cells in their hexagonal layout,
+//! each drawn on two lines, its index and richness above and its tree
+//! below. Our trees are M and the opponent's X followed by their size, with
+//! z when dormant. With ANSI colors richness is coloured from grey
+//! (unusable) to bright green and the owners blue and red, otherwise the
+//! output is plain ASCII.
+//!
+//! The shadow overlay marks the cells shaded on the next day with ~ and the
+//! size of the largest shadow, in bold when it spooks the tree there.
+
+use std::fmt::Write;
+
+use crate::config::flag;
+use crate::game::{Area, Tree};
+use crate::hex::{cube_coordinates, BOARD_RADIUS};
+use crate::rules::{shadows, State};
+
+pub const DIRECTION_NAMES: [&str; 6] = ["E", "NE", "NW", "W", "SW", "SE"];
+const DIRECTION_ARROWS: [&str; 6] = ["→", "↗", "↖", "←", "↙", "↘"];
+
+// Characters per half cell, neighbours on a row being two halves apart
+const HALF_CELL: usize = 4;
+
+const RESET: &str = "\x1b[0m";
+const RICHNESS_COLORS: [&str; 4] = ["\x1b[90m", "\x1b[33m", "\x1b[32m", "\x1b[92m"];
+const OWNER_COLORS: [&str; 2] = ["\x1b[1;34m", "\x1b[1;31m"];
+const DORMANT: &str = "\x1b[2m";
+const SHADOW: &str = "\x1b[35m";
+const SPOOKED: &str = "\x1b[1;35m";
+
+#[derive(Copy, Clone)]
+pub struct RenderOptions {
+    // ANSI colors, plain ASCII without
+    pub colors: bool,
+    // mark the cells shaded on the next day
+    pub shadows: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions { colors: true, shadows: false }
+    }
+}
+
+impl RenderOptions {
+    // Colors unless `--ascii`, the overlay with `--shadows`
+    pub fn from_settings() -> RenderOptions {
+        RenderOptions { colors: !flag("ascii"), shadows: flag("shadows") }
+    }
+}
+
+// Text with `color` when colors are on, padded to `width` visible characters
+fn cell_text(text: &str, color: &str, width: usize, options: &RenderOptions) -> String {
+    let padded = format!("{:<width$}", text, width = width);
+    if options.colors && !color.is_empty() {
+        format!("{}{}{}", color, padded, RESET)
+    } else {
+        padded
+    }
+}
+
+fn tree_text(tree: &Tree, options: &RenderOptions) -> String {
+    let owner = if tree.is_mine { 0 } else { 1 };
+    let text = format!("{}{}{}", ["M", "X"][owner], tree.size, if tree.is_dormant { "z" } else { "" });
+    let color = if tree.is_dormant && options.colors { format!("{}{}", OWNER_COLORS[owner], DORMANT) } else { OWNER_COLORS[owner].to_string() };
+    cell_text(&text, &color, 3, options)
+}
+
+pub fn render(state: &State, board: &Area, options: &RenderOptions) -> String {
+    let coordinates = cube_coordinates(board);
+    let tomorrow = state.day + 1;
+    let shaded = if options.shadows { shadows(board, &state.forest, tomorrow) } else { Default::default() };
+
+    let mut output = String::new();
+    let direction = (state.day % 6) as usize;
+    let arrow = if options.colors { format!(" {}", DIRECTION_ARROWS[direction]) } else { String::new() };
+    writeln!(
+        output,
+        "Day {}  nutrients {}  sun {} / {}  score {} / {}{}  shadows {}{}",
+        state.day,
+        state.nutrients,
+        state.sun[0],
+        state.sun[1],
+        state.score[0],
+        state.score[1],
+        match state.waiting {
+            [true, true] => "  both asleep",
+            [true, false] => "  M asleep",
+            [false, true] => "  X asleep",
+            [false, false] => "",
+        },
+        DIRECTION_NAMES[direction],
+        arrow
+    )
+    .unwrap();
+
+    // Rows from top to bottom, each cell at twice its x plus its row in half cells
+    let radius = BOARD_RADIUS;
+    for row in -radius..=radius {
+        let mut cells: Vec<(i32, i32)> = coordinates.iter().filter(|(_, cube)| cube.2 == row).map(|(index, cube)| (2 * cube.0 + row, *index)).collect();
+        cells.sort();
+
+        let mut lines = [String::new(), String::new()];
+        let mut column = 0;
+        for (position, index) in cells {
+            let start = (position + 2 * radius) as usize * HALF_CELL;
+            for line in lines.iter_mut() {
+                line.push_str(&" ".repeat(start - column));
+            }
+            column = start + 2 * HALF_CELL - 1;
+
+            let richness = board[&index].richness;
+            let label = if richness == 0 { format!("{:>2} --", index) } else { format!("{:>2} r{}", index, richness) };
+            lines[0].push_str(&cell_text(&label, RICHNESS_COLORS[richness as usize], 2 * HALF_CELL - 1, options));
+
+            let tree = state.forest.get(&index);
+            lines[1].push_str(&match tree {
+                Some(tree) => tree_text(tree, options),
+                None => cell_text(" .", "", 3, options),
+            });
+            match shaded.get(&index) {
+                Some(shadow) => {
+                    let spooked = tree.map_or(false, |tree| tree.size > 0 && *shadow >= tree.size);
+                    let color = if spooked { SPOOKED } else { SHADOW };
+                    lines[1].push_str(&cell_text(&format!("~{}", shadow), color, 2 * HALF_CELL - 4, options));
+                }
+                None => lines[1].push_str(&" ".repeat(2 * HALF_CELL - 4)),
+            }
+        }
+        for line in lines {
+            writeln!(output, "{}", line.trim_end()).unwrap();
+        }
+    }
+
+    if options.shadows {
+        writeln!(output, "~n: shadow of size n on day {}, cast {}", tomorrow, DIRECTION_NAMES[(tomorrow % 6) as usize]).unwrap();
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapgen::build_board;
+    use crate::test_util::position;
+
+    // Our large tree in the center shades the row west of it on day 3,
+    // including the opponent's dormant seedling next to it
+    #[test]
+    fn plain_text_with_shadows() {
+        let board = build_board(&[]);
+        let state = position(2).sun([5, 3]).trees(&[(0, 3, true), (4, 1, false), (25, 2, true)]).dormant(&[4]).state();
+        let output = render(&state, &board, &RenderOptions { colors: false, shadows: true });
+        let expected = [
+            "Day 2  nutrients 20  sun 5 / 3  score 0 / 0  shadows NW",
+            "            25 r1   24 r1   23 r1   22 r1",
+            "            M2       .       .       .",
+            "        26 r1   11 r2   10 r2    9 r2   21 r1",
+            "         .       .       .       .       .",
+            "    27 r1   12 r2    3 r3    2 r3    8 r2   20 r1",
+            "     .       .       .       .       .       .",
+            "28 r1   13 r2    4 r3    0 r3    1 r3    7 r2   19 r1",
+            " . ~3    . ~3   X1z~3   M3       .       .       .",
+            "    29 r1   14 r2    5 r3    6 r3   18 r2   36 r1",
+            "     .       .       .       .       .       .",
+            "        30 r1   15 r2   16 r2   17 r2   35 r1",
+            "         .       .       .       .       .",
+            "            31 r1   32 r1   33 r1   34 r1",
+            "             .       .       .       .",
+            "~n: shadow of size n on day 3, cast W",
+        ];
+        assert_eq!(output.lines().collect::<Vec<_>>(), expected);
+    }
+}
@@ -0,0 +1,65 @@
+//! Small and fast xorshift PRNG for the searches, seedable so that a
+//! decision can be reproduced.
+
+use rand::{Error, Rng, RngCore, SeedableRng};
+
+use crate::config::parsed_setting;
+
+pub struct Xorshift {
+    state: u64,
+}
+
+impl Xorshift {
+    pub fn new(seed: u64) -> Xorshift {
+        // Spread the seed with a splitmix64 step, xorshift must not start at 0
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        Xorshift { state: if z == 0 { 0x9E37_79B9_7F4A_7C15 } else { z } }
+    }
+
+    // Seed given with `--seed` or RUSTBOT_SEED, otherwise from entropy
+    pub fn from_settings() -> (Xorshift, u64) {
+        let seed = seed_from_settings();
+        (Xorshift::new(seed), seed)
+    }
+}
+
+impl RngCore for Xorshift {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    // xorshift64*
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for Xorshift {
+    type Seed = [u8; 8];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        Xorshift::new(u64::from_le_bytes(seed))
+    }
+}
+
+pub fn seed_from_settings() -> u64 {
+    parsed_setting("seed").unwrap_or_else(|| rand::thread_rng().gen())
+}
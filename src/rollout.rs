@@ -0,0 +1,201 @@
+//! Random playouts where both players pick their actions simultaneously and
+//! the referee resolves them together.
+//!
+//! Actions are sampled with probability proportional to
+//! exp(heuristic score / temperature), so playouts resemble plausible play.
+//! An infinite temperature gives uniform random playouts and a temperature
+//! of zero always picks the best scored action. The opponent's weights can
+//! further be biased by what we learnt of their play.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::config::parsed_setting;
+use crate::game::{Action, Area};
+use crate::heuristics::{calculate_game_points_from_action, evaluate_leaf};
+use crate::opponent_model::OpponentBias;
+use crate::rules::{apply_joint_actions, possible_actions, State, LAST_DAY};
+use crate::weights::EvalWeights;
+
+// The horizon and temperature are weights, so that they are tuned and baked
+// with the evaluation
+#[derive(Copy, Clone)]
+pub struct PlayoutConfig {
+    pub opponent: Option<OpponentBias>,
+    pub weights: EvalWeights,
+}
+
+impl Default for PlayoutConfig {
+    fn default() -> Self {
+        PlayoutConfig { opponent: None, weights: EvalWeights::baked() }
+    }
+}
+
+impl PlayoutConfig {
+    // Weights from the `weights` setting, with the `temperature` and `horizon`
+    // settings over them
+    pub fn from_settings() -> PlayoutConfig {
+        let mut weights = EvalWeights::from_settings();
+        weights.temperature = parsed_setting("temperature").unwrap_or(weights.temperature);
+        weights.horizon = parsed_setting("horizon").unwrap_or(weights.horizon);
+        PlayoutConfig { opponent: None, weights }
+    }
+
+    // Day at which rollouts starting on `day` stop
+    pub fn last_day(&self, day: i32) -> i32 {
+        (day + self.weights.horizon).min(LAST_DAY + 1)
+    }
+}
+
+#[derive(Clone)]
+pub struct Rollout {
+    pub state: State,
+    // game score shaped by the action heuristics
+    pub points: [i32; 2],
+    // joint actions played so far when recording, Null for a sleeping player
+    pub history: Option<Vec<[Action; 2]>>,
+}
+
+impl Rollout {
+    pub fn new(state: State) -> Rollout {
+        let points = state.score;
+        Rollout { state, points, history: None }
+    }
+
+    pub fn recorded(state: State) -> Rollout {
+        Rollout { history: Some(vec![]), ..Rollout::new(state) }
+    }
+
+    pub fn step(&mut self, board: &Area, weights: &EvalWeights, actions: [Action; 2]) {
+        let mut played = [Action::Null; 2];
+        for (player, action) in actions.iter().enumerate() {
+            if !self.state.waiting[player] {
+                self.points[player] += calculate_game_points_from_action(action, self.state.nutrients, board, &self.state.forest, self.state.day, player, weights);
+                played[player] = *action;
+            }
+        }
+        if let Some(history) = &mut self.history {
+            history.push(played);
+        }
+
+        if let Some(gathered) = apply_joint_actions(&mut self.state, board, actions) {
+            if self.state.is_game_over() {
+                for player in 0..2 {
+                    self.points[player] += self.state.sun[player] / 3;
+                }
+            } else if self.state.day < weights.sun_as_points_until_day {
+                for (points, sun) in self.points.iter_mut().zip(gathered) {
+                    *points += sun;
+                }
+            }
+        }
+    }
+
+    // Plays joint actions from the policy until the game ends or `last_day` is
+    // reached, in which case the position left is evaluated
+    pub fn play_out<R: Rng>(&mut self, board: &Area, config: &PlayoutConfig, rng: &mut R, last_day: i32) {
+        while !self.state.is_game_over() && self.state.day < last_day {
+            let actions = [
+                choose_rollout_action(&self.state, board, 0, config, rng),
+                choose_rollout_action(&self.state, board, 1, config, rng),
+            ];
+            self.step(board, &config.weights, actions);
+        }
+
+        if !self.state.is_game_over() {
+            for player in 0..2 {
+                self.points[player] += evaluate_leaf(&self.state, board, player, &config.weights);
+            }
+        }
+    }
+}
+
+// Seeding is only considered with few trees and early enough in the game
+pub fn choose_rollout_action<R: Rng>(state: &State, board: &Area, player: usize, config: &PlayoutConfig, rng: &mut R) -> Action {
+    // A sleeping player has no turn until the next day
+    if state.waiting[player] {
+        return Action::Wait;
+    }
+
+    let weights = &config.weights;
+    // The last seeding day is drawn from the fifth on, whatever the weight
+    let roll = rng.gen_range(5..weights.seed_until_day.max(5) + 1);
+    let allow_seeds = state.total_trees(player) < weights.seed_max_trees && state.day < roll;
+    let possible_choices = possible_actions(state, board, player, allow_seeds);
+    let bias = config.opponent.filter(|_| player == 1);
+
+    if possible_choices.len() == 1 || (weights.temperature.is_infinite() && bias.is_none()) {
+        return *possible_choices.choose(rng).unwrap();
+    }
+
+    let scores: Vec<f64> = possible_choices
+        .iter()
+        .map(|action| calculate_game_points_from_action(action, state.nutrients, board, &state.forest, state.day, player, weights) as f64)
+        .collect();
+    let max_score = scores.iter().cloned().fold(f64::MIN, f64::max);
+
+    // Without a temperature to scale it, the bias counts as it would at
+    // temperature 1: ln(factor) added to the score
+    if weights.temperature <= 0.0 {
+        let biased = |i: usize| scores[i] + bias.map_or(0.0, |bias| bias.factor(&possible_choices[i], state.day, board).ln());
+        let best = (0..scores.len()).fold(0, |best, i| if biased(i) > biased(best) { i } else { best });
+        return possible_choices[best];
+    }
+
+    // Shifted by the max score so that exp() cannot overflow
+    let weights: Vec<f64> = scores
+        .iter()
+        .zip(&possible_choices)
+        .map(|(score, action)| {
+            let weight = ((score - max_score) / weights.temperature).exp();
+            bias.map_or(weight, |bias| weight * bias.factor(action, state.day, board))
+        })
+        .collect();
+    let mut roll = rng.gen::<f64>() * weights.iter().sum::<f64>();
+    for (action, weight) in possible_choices.iter().zip(weights) {
+        if roll < weight {
+            return *action;
+        }
+        roll -= weight;
+    }
+    *possible_choices.last().unwrap()
+}
+
+// Deterministic version of the seed gating above, used where the set of
+// actions must stay the same between visits
+pub fn seeds_allowed(state: &State, player: usize, weights: &EvalWeights) -> bool {
+    state.total_trees(player) < weights.seed_max_trees && state.day < weights.seed_until_day.max(5)
+}
+
+// Our candidate actions at the root of a search, restricted to what the
+// referee says is legal this turn
+pub fn root_actions(state: &State, board: &Area, expected_actions: &[Action], weights: &EvalWeights) -> Vec<Action> {
+    let mut choices = possible_actions(state, board, 0, seeds_allowed(state, 0, weights));
+    choices.retain(|a| expected_actions.contains(a));
+    if choices.is_empty() {
+        choices = expected_actions.to_vec();
+    }
+    choices.sort();
+    choices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapgen::build_board;
+    use crate::rng::Xorshift;
+    use crate::test_util::position;
+
+    #[test]
+    fn seeding_ends_on_day_5_at_the_earliest() {
+        let board = build_board(&[]);
+        let state = position(3).sun([5, 5]).trees(&[(0, 2, true), (4, 2, false)]).state();
+        let mut config = PlayoutConfig::default();
+        config.weights.seed_until_day = 0;
+        config.weights.temperature = f64::INFINITY;
+
+        let mut rng = Xorshift::new(1);
+        let seeded = (0..100).any(|_| matches!(choose_rollout_action(&state, &board, 0, &config, &mut rng), Action::Seed(..)));
+        assert!(seeded);
+    }
+}
@@ -0,0 +1,470 @@
+//! Rules of the game: move generation, costs, sun gathering and the referee
+//! that resolves both players' actions simultaneously.
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::iter;
+
+use crate::game::{Action, Area, FixedState, Forest, GameContext, Tree};
+
+// the game lasts 24 days: 0-23
+pub const LAST_DAY: i32 = 23;
+
+// Player 0 is always us, player 1 the opponent.
+pub fn is_owned_by(tree: &Tree, player: usize) -> bool {
+    tree.is_mine == (player == 0)
+}
+
+#[derive(Clone)]
+pub struct State {
+    pub day: i32,
+    pub nutrients: i32,
+    pub sun: [i32; 2],
+    pub score: [i32; 2],
+    pub waiting: [bool; 2],
+    pub forest: Forest,
+    pub trees_counts: [[i32; 4]; 2],
+}
+
+impl State {
+    pub fn new(context: &GameContext, forest: &Forest) -> State {
+        let mut trees_counts = [[0; 4]; 2];
+        for tree in forest.values() {
+            let player = if tree.is_mine { 0 } else { 1 };
+            trees_counts[player][tree.size as usize] += 1;
+        }
+
+        State {
+            day: context.day,
+            nutrients: context.nutrients,
+            sun: [context.sun, context.op_sun],
+            score: [context.score, context.op_score],
+            // we are never asleep when asked to play
+            waiting: [false, context.op_is_waiting],
+            forest: forest.clone(),
+            trees_counts,
+        }
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.day > LAST_DAY
+    }
+
+    // Whether both states describe the same position
+    pub fn same_position(&self, other: &State) -> bool {
+        self.day == other.day
+            && self.nutrients == other.nutrients
+            && self.sun == other.sun
+            && self.score == other.score
+            && self.waiting == other.waiting
+            && self.forest == other.forest
+    }
+
+    // The same position seen from the opponent's side
+    pub fn swapped(&self) -> State {
+        let mut forest = self.forest.clone();
+        for tree in forest.values_mut() {
+            tree.is_mine = !tree.is_mine;
+        }
+
+        State {
+            day: self.day,
+            nutrients: self.nutrients,
+            sun: [self.sun[1], self.sun[0]],
+            score: [self.score[1], self.score[0]],
+            waiting: [self.waiting[1], self.waiting[0]],
+            forest,
+            trees_counts: [self.trees_counts[1], self.trees_counts[0]],
+        }
+    }
+
+    pub fn total_trees(&self, player: usize) -> i32 {
+        self.trees_counts[player].iter().sum()
+    }
+
+    // Player with the higher score once the game is over, the number of trees
+    // breaking ties; None for a draw
+    pub fn winner(&self) -> Option<usize> {
+        let standing = |player: usize| (self.score[player], self.total_trees(player));
+        match standing(0).cmp(&standing(1)) {
+            Ordering::Greater => Some(0),
+            Ordering::Less => Some(1),
+            Ordering::Equal => None,
+        }
+    }
+}
+
+/// The pruned set of actions the search considers for `player`: WAIT is only
+/// offered when sun is low or nothing else is possible.
+pub fn possible_actions(state: &State, board: &Area, player: usize, allow_seeds: bool) -> Vec<Action> {
+    if state.waiting[player] {
+        return vec![Action::Wait];
+    }
+
+    let sun = state.sun[player];
+    let mut possible_choices = calculate_grow_actions(sun, &state.forest, state.trees_counts[player], player);
+    possible_choices.append(&mut calculate_complete_actions(sun, &state.forest, player));
+    if allow_seeds {
+        possible_choices.append(&mut calculate_seed_actions(sun, &state.forest, board, state.trees_counts[player], player));
+    }
+
+    if sun < 4 || possible_choices.is_empty() {
+        possible_choices.push(Action::Wait);
+    }
+    possible_choices
+}
+
+/// Every action the referee accepts from `player`, as listed in the input.
+pub fn legal_actions(state: &State, board: &Area, player: usize) -> Vec<Action> {
+    if state.waiting[player] {
+        return vec![];
+    }
+
+    let sun = state.sun[player];
+    let mut actions = vec![Action::Wait];
+    actions.append(&mut calculate_seed_actions(sun, &state.forest, board, state.trees_counts[player], player));
+    actions.append(&mut calculate_grow_actions(sun, &state.forest, state.trees_counts[player], player));
+    actions.append(&mut calculate_complete_actions(sun, &state.forest, player));
+    actions
+}
+
+/// Resolves one simultaneous turn. A player that is already asleep ignores its
+/// action. Returns the sun gathered by each player if a new day started.
+pub fn apply_joint_actions(state: &mut State, board: &Area, actions: [Action; 2]) -> Option<[i32; 2]> {
+    let mut completed = 0;
+
+    // Both players planting on the same cell cancels both seeds, refunding the sun
+    let seed_conflict = match actions {
+        [Action::Seed(a, _), Action::Seed(b, _)] => a == b && !state.waiting[0] && !state.waiting[1],
+        _ => false,
+    };
+
+    for (player, action) in actions.iter().enumerate() {
+        if state.waiting[player] {
+            continue;
+        }
+
+        match action {
+            Action::Grow(cell_index) => {
+                state.sun[player] -= get_cost_of_action(action, &state.forest, state.trees_counts[player]);
+                let mut tree: Tree = state.forest[cell_index];
+                state.trees_counts[player][tree.size as usize] -= 1;
+                tree.size += 1;
+                state.trees_counts[player][tree.size as usize] += 1;
+                tree.is_dormant = true;
+                state.forest.insert(*cell_index, tree);
+            }
+            Action::Seed(target_cell_index, origin_cell_index) => {
+                if !seed_conflict {
+                    state.sun[player] -= get_cost_of_action(action, &state.forest, state.trees_counts[player]);
+                    state.forest.insert(*target_cell_index, Tree { cell_index: *target_cell_index, size: 0, is_mine: player == 0, is_dormant: true });
+                    state.trees_counts[player][0] += 1;
+                }
+                if let Some(tree) = state.forest.get_mut(origin_cell_index) {
+                    tree.is_dormant = true;
+                }
+            }
+            Action::Complete(cell_index) => {
+                state.sun[player] -= get_cost_of_action(action, &state.forest, state.trees_counts[player]);
+                state.score[player] += state.nutrients + richness_bonus(board[cell_index].richness);
+                state.trees_counts[player][state.forest[cell_index].size as usize] -= 1;
+                state.forest.remove(cell_index);
+                completed += 1;
+            }
+            Action::Wait => state.waiting[player] = true,
+            Action::Null => ()
+        }
+    }
+
+    // Both players harvesting in the same turn get the same nutrients
+    state.nutrients = (state.nutrients - completed).max(0);
+
+    if state.waiting[0] && state.waiting[1] {
+        return Some(start_new_day(state, board));
+    }
+    None
+}
+
+fn start_new_day(state: &mut State, board: &Area) -> [i32; 2] {
+    state.day += 1;
+    state.waiting = [false, false];
+    for tree in state.forest.values_mut() {
+        tree.is_dormant = false;
+    }
+
+    if state.is_game_over() {
+        for player in 0..2 {
+            state.score[player] += state.sun[player] / 3;
+        }
+        return [0, 0];
+    }
+
+    let mut gathered = [0, 0];
+    for (player, sun) in gathered.iter_mut().enumerate() {
+        *sun = get_my_sun_points(board, &state.day, &state.forest, player);
+        state.sun[player] += *sun;
+    }
+    gathered
+}
+
+pub fn richness_bonus(richness: i32) -> i32 {
+    match richness {
+        2 => 2,
+        3 => 4,
+        _ => 0
+    }
+}
+
+pub fn calculate_seed_actions(sun_points: i32, trees: &Forest, board: &Area, my_trees_counts: [i32; 4], player: usize) -> Vec<Action> {
+    let planted_seeds = my_trees_counts[0];
+
+    if sun_points < planted_seeds {
+        return vec![];
+    }
+
+    let mut result: HashSet<(i32, i32), FixedState> = HashSet::default();
+
+    for tree in trees.values() {
+        if !is_owned_by(tree, player) {
+            continue;
+        }
+        if tree.is_dormant || tree.size == 0 {
+            continue;
+        }
+
+        let mut explore_cells: HashSet<i32, FixedState> = HashSet::default();
+        explore_cells.insert(tree.cell_index);
+
+        for _ in 0..tree.size {
+            let mut plantable_cells: HashSet<i32, FixedState> = HashSet::default();
+            let mut next_explorable_cells: HashSet<i32, FixedState> = HashSet::default();
+
+            for ex_cell in explore_cells {
+                let neighbours: Vec<i32> = board[&ex_cell].neighbors_ids.iter().filter(|i| **i != -1 && board[i].richness > 0 && !trees.contains_key(i)).copied().collect();
+
+                plantable_cells.extend(&neighbours);
+                next_explorable_cells.extend(board[&ex_cell].neighbors_ids.iter().filter(|i| **i != -1))
+            }
+
+            result.extend(vec!(tree.cell_index; plantable_cells.len()).into_iter().zip(plantable_cells));
+            explore_cells = next_explorable_cells
+        }
+    }
+
+    result
+        .iter()
+        .map(|(origin_id, target_id)| Action::Seed(*target_id, *origin_id))
+        .collect()
+}
+
+pub fn calculate_complete_actions(sun_points: i32, trees: &Forest, player: usize) -> Vec<Action> {
+    if sun_points < 4 {
+        return vec![];
+    }
+
+    trees
+        .values()
+        .filter(|tree| is_owned_by(tree, player) && !tree.is_dormant && tree.size == 3)
+        .map(|tree| Action::Complete(tree.cell_index))
+        .collect()
+}
+
+pub fn calculate_grow_actions(sun_points: i32, trees: &Forest, my_trees_counts: [i32; 4], player: usize) -> Vec<Action> {
+    let tree_costs = [my_trees_counts[0], 1 + my_trees_counts[1], 3 + my_trees_counts[2], 7 + my_trees_counts[3]];
+
+    trees
+        .iter()
+        .filter(|(_, tree)| is_owned_by(tree, player) && !tree.is_dormant && tree.size < 3 && tree_costs[(tree.size + 1) as usize] <= sun_points)
+        .map(|(id, _)| Action::Grow(*id))
+        .collect()
+}
+
+pub fn get_cost_of_action(action: &Action, trees: &Forest, my_trees_counts: [i32; 4]) -> i32 {
+    match action {
+        Action::Complete(_) => 4,
+        Action::Seed(_, _) => my_trees_counts[0],
+        Action::Grow(target_cell_id) => {
+            let tree_size = trees[target_cell_id].size + 1;
+            let extra_cost = my_trees_counts[tree_size as usize];
+
+            match tree_size {
+                1 => 1 + extra_cost,
+                2 => 3 + extra_cost,
+                3 => 7 + extra_cost,
+                _ => 0
+            }
+        }
+        _ => 0
+    }
+}
+
+pub fn get_my_sun_points(board: &Area, day: &i32, trees: &Forest, player: usize) -> i32 {
+    // get sun points from trees not in shadow
+    let sun_direction = (day + 3) % 6;
+
+    trees
+        .values()
+        .filter(|tree| is_owned_by(tree, player) && tree.size > 0)
+        .map(|tree| get_sun_points_from_tree(tree, board, trees, sun_direction))
+        .sum()
+}
+
+// Size of the largest shadow over each shaded cell on `day`: every tree
+// shades as many cells as its size in direction day % 6
+pub fn shadows(board: &Area, trees: &Forest, day: i32) -> HashMap<i32, i32> {
+    let shadow_direction = (day % 6) as usize;
+    let mut shadows = HashMap::new();
+
+    for tree in trees.values() {
+        for cell_index in cells_in_direction(board, tree.cell_index, shadow_direction, tree.size) {
+            let shadow = shadows.entry(cell_index).or_insert(0);
+            *shadow = tree.size.max(*shadow);
+        }
+    }
+    shadows
+}
+
+pub fn get_sun_points_from_tree(tree: &Tree, board: &Area, trees: &Forest, sun_direction: i32) -> i32 {
+    let shaded = cells_in_direction(board, tree.cell_index, sun_direction as usize, 3)
+        .enumerate()
+        .any(|(i, cell_index)| trees.get(&cell_index).map_or(false, |other| other.size > i as i32 && other.size >= tree.size));
+    if shaded {
+        0
+    } else {
+        tree.size
+    }
+}
+
+// The next cells from a cell in a direction, up to a distance or the edge of
+// the board
+fn cells_in_direction(board: &Area, cell_index: i32, direction: usize, distance: i32) -> impl Iterator<Item = i32> + '_ {
+    iter::successors(Some(cell_index), move |cell_index| Some(board[cell_index].neighbors_ids[direction]).filter(|next| *next != -1))
+        .skip(1)
+        .take(distance as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapgen::build_board;
+    use crate::test_util::position;
+
+    #[test]
+    fn seed_conflict_refunds_both_players() {
+        let board = build_board(&[]);
+        // One seed each already, so seeding costs 1
+        let mut state = position(5).sun([6, 6]).trees(&[(1, 2, true), (4, 2, false), (7, 0, true), (13, 0, false)]).state();
+
+        apply_joint_actions(&mut state, &board, [Action::Seed(0, 1), Action::Seed(0, 4)]);
+
+        assert_eq!(state.sun, [6, 6]);
+        assert!(!state.forest.contains_key(&0));
+        assert_eq!(state.trees_counts[0][0], 1);
+        assert_eq!(state.trees_counts[1][0], 1);
+        assert!(state.forest[&1].is_dormant && state.forest[&4].is_dormant);
+    }
+
+    #[test]
+    fn seeds_on_different_cells_are_both_planted() {
+        let board = build_board(&[]);
+        let mut state = position(5).sun([6, 6]).trees(&[(1, 2, true), (4, 2, false)]).state();
+
+        apply_joint_actions(&mut state, &board, [Action::Seed(0, 1), Action::Seed(3, 4)]);
+
+        assert!(state.forest[&0].is_mine && !state.forest[&3].is_mine);
+        assert_eq!(state.trees_counts[0][0], 1);
+        assert_eq!(state.trees_counts[1][0], 1);
+    }
+
+    #[test]
+    fn simultaneous_completes_share_the_nutrients() {
+        let board = build_board(&[]);
+        let mut state = position(12).nutrients(10).sun([5, 4]).trees(&[(1, 3, true), (10, 3, false)]).state();
+
+        apply_joint_actions(&mut state, &board, [Action::Complete(1), Action::Complete(10)]);
+
+        // Richness 3 gives 4 extra points, richness 2 gives 2
+        assert_eq!(state.score, [14, 12]);
+        assert_eq!(state.nutrients, 8);
+        assert_eq!(state.sun, [1, 0]);
+        assert!(state.forest.is_empty());
+        assert_eq!(state.trees_counts, [[0; 4]; 2]);
+    }
+
+    #[test]
+    fn nutrients_do_not_drop_below_zero() {
+        let board = build_board(&[]);
+        let mut state = position(20).nutrients(1).sun([4, 4]).trees(&[(1, 3, true), (4, 3, false)]).state();
+
+        apply_joint_actions(&mut state, &board, [Action::Complete(1), Action::Complete(4)]);
+
+        assert_eq!(state.score, [5, 5]);
+        assert_eq!(state.nutrients, 0);
+    }
+
+    #[test]
+    fn taller_trees_shade_shorter_ones() {
+        let board = build_board(&[]);
+        // On day 0 shadows are cast east, from cell 4 over the center to cell 1
+        let west = board[&4].neighbors_ids[3];
+
+        let state = position(0).trees(&[(4, 3, false), (0, 2, true)]).state();
+        assert_eq!(get_my_sun_points(&board, &0, &state.forest, 0), 0);
+        assert_eq!(get_my_sun_points(&board, &0, &state.forest, 1), 3);
+
+        let state = position(0).trees(&[(4, 1, false), (0, 2, true)]).state();
+        assert_eq!(get_my_sun_points(&board, &0, &state.forest, 0), 2);
+
+        // A tree of the same size shades, but not past its own length
+        let state = position(0).trees(&[(west, 2, false), (0, 2, true), (1, 2, true)]).state();
+        assert_eq!(get_my_sun_points(&board, &0, &state.forest, 0), 0);
+        assert_eq!(get_my_sun_points(&board, &0, &state.forest, 1), 2);
+
+        let shaded = shadows(&board, &state.forest, 0);
+        assert_eq!(shaded[&4], 2);
+        assert_eq!(shaded[&0], 2);
+        assert_eq!(shaded[&1], 2);
+        assert!(!shaded.contains_key(&west));
+    }
+
+    #[test]
+    fn new_day_gathers_sun_in_the_new_direction() {
+        let board = build_board(&[]);
+        // Shaded on day 0, but on day 1 the shadows are cast north east
+        let mut state = position(0).trees(&[(4, 3, false), (0, 2, true)]).state();
+
+        let gathered = apply_joint_actions(&mut state, &board, [Action::Wait, Action::Wait]);
+
+        assert_eq!(gathered, Some([2, 3]));
+        assert_eq!(state.day, 1);
+        assert_eq!(state.sun, [2, 3]);
+        assert_eq!(state.waiting, [false, false]);
+    }
+
+    #[test]
+    fn sun_is_scored_after_the_last_day() {
+        let board = build_board(&[]);
+        let mut state = position(LAST_DAY).sun([10, 5]).trees(&[(0, 3, true), (4, 3, false)]).state();
+
+        let gathered = apply_joint_actions(&mut state, &board, [Action::Wait, Action::Wait]);
+
+        assert!(state.is_game_over());
+        assert_eq!(gathered, Some([0, 0]));
+        assert_eq!(state.score, [3, 1]);
+        assert_eq!(state.winner(), Some(0));
+    }
+
+    #[test]
+    fn a_sleeping_player_ignores_its_action() {
+        let board = build_board(&[]);
+        let mut state = position(3).sun([10, 10]).trees(&[(0, 1, true), (4, 1, false)]).state();
+
+        apply_joint_actions(&mut state, &board, [Action::Wait, Action::Grow(4)]);
+        assert_eq!(state.sun, [10, 7]);
+        apply_joint_actions(&mut state, &board, [Action::Grow(0), Action::Wait]);
+
+        assert_eq!(state.day, 4);
+        assert_eq!(state.forest[&0].size, 1);
+        assert_eq!(state.forest[&4].size, 2);
+    }
+}
@@ -0,0 +1,32 @@
+//! Games between two bots in the same process, refereed by the simulation of
+//! the rules. Each bot sees the game as player 0, the way the referee's
+//! input presents it.
+
+use crate::bot::Bot;
+use crate::game::{Action, Area};
+use crate::rules::{apply_joint_actions, legal_actions, State};
+use crate::time::Deadline;
+
+// Plays the game from `state` to the end with bots[0] as player 0, and
+// returns the final position. `deadline` gives the limit of each decision.
+pub fn play_game(board: &Area, mut state: State, bots: &mut [Box<dyn Bot>; 2], deadline: impl Fn() -> Deadline) -> State {
+    for bot in bots.iter_mut() {
+        bot.init(board);
+    }
+
+    while !state.is_game_over() {
+        let mut actions = [Action::Wait; 2];
+        for (player, bot) in bots.iter_mut().enumerate() {
+            if state.waiting[player] {
+                continue;
+            }
+            let view = if player == 0 { state.clone() } else { state.swapped() };
+            let legal = legal_actions(&view, board, 0);
+            let action = bot.decide(&view, &legal, deadline()).action;
+            // The referee would disqualify, the simulation lets the bot wait
+            actions[player] = if legal.contains(&action) { action } else { Action::Wait };
+        }
+        apply_joint_actions(&mut state, board, actions);
+    }
+    state
+}
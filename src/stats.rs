@@ -0,0 +1,98 @@
+//! Statistics of the actions at the root of a search, and the policies
+//! picking the final move from them.
+
+use std::str::FromStr;
+
+use crate::game::Action;
+
+// z-score of the 95% confidence intervals
+const CONFIDENCE_Z: f64 = 1.96;
+
+// Half width of the 95% confidence interval of the mean of `count` samples
+pub fn confidence_radius(variance: f64, count: f64) -> f64 {
+    CONFIDENCE_Z * (variance / count).sqrt()
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FinalMovePolicy {
+    MaxMean,
+    MaxVisits,
+    // best pessimistic estimate, so a lucky action tried a few times cannot win
+    LowerConfidenceBound,
+}
+
+impl FromStr for FinalMovePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mean" => Ok(FinalMovePolicy::MaxMean),
+            "visits" => Ok(FinalMovePolicy::MaxVisits),
+            "lcb" => Ok(FinalMovePolicy::LowerConfidenceBound),
+            _ => Err(format!("Unknown final move policy {}", s)),
+        }
+    }
+}
+
+// Running count, mean and variance of the rollout scores of one action
+#[derive(Copy, Clone, Default)]
+pub struct ActionStats {
+    pub count: i32,
+    pub mean: f64,
+    // sum of squared differences from the mean (Welford)
+    m2: f64,
+}
+
+impl ActionStats {
+    // Statistics of `count` scores summing to `total`, and their squares to
+    // `sum_squares`
+    pub fn from_sums(count: u32, total: f64, sum_squares: f64) -> ActionStats {
+        if count == 0 {
+            return ActionStats::default();
+        }
+        let mean = total / count as f64;
+        ActionStats { count: count as i32, mean, m2: (sum_squares - total * mean).max(0.0) }
+    }
+
+    pub fn add(&mut self, score: f64) {
+        self.count += 1;
+        let delta = score - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (score - self.mean);
+    }
+
+    pub fn variance(&self) -> f64 {
+        if self.count < 2 { f64::INFINITY } else { self.m2 / (self.count - 1) as f64 }
+    }
+
+    // Half width of the confidence interval around the mean
+    pub fn confidence_radius(&self) -> f64 {
+        confidence_radius(self.variance(), self.count as f64)
+    }
+
+    pub fn lower_bound(&self) -> f64 {
+        self.mean - self.confidence_radius()
+    }
+
+    pub fn upper_bound(&self) -> f64 {
+        self.mean + self.confidence_radius()
+    }
+
+    pub fn value(&self, policy: FinalMovePolicy) -> f64 {
+        match policy {
+            FinalMovePolicy::MaxMean => self.mean,
+            FinalMovePolicy::MaxVisits => self.count as f64,
+            FinalMovePolicy::LowerConfidenceBound => self.lower_bound(),
+        }
+    }
+}
+
+// Table of the root statistics on stderr, best mean first
+pub fn print_statistics(mut rows: Vec<(Action, ActionStats)>) {
+    rows.sort_by(|(_, a), (_, b)| b.mean.partial_cmp(&a.mean).unwrap());
+
+    eprintln!("{:<14} {:>6} {:>8} {:>8} {:>8} {:>8}", "action", "count", "mean", "stddev", "low", "high");
+    for (action, stats) in rows {
+        eprintln!("{:<14} {:>6} {:>8.2} {:>8.2} {:>8.2} {:>8.2}", action.to_string(), stats.count, stats.mean, stats.variance().sqrt(), stats.lower_bound(), stats.upper_bound());
+    }
+}
@@ -0,0 +1,194 @@
+//! SVG drawing of a position, for sharing in reviews: hexes coloured by
+//! richness, trees as discs sized by their size in the owner's colour,
+//! dormant trees with a dashed outline and a z, the cells shaded on the
+//! next day darkened with the size of the shadow, and the chosen action
+//! outlined with its message as caption.
+
+use std::fmt::Write;
+
+use crate::bot::Decision;
+use crate::game::{Action, Area, Tree};
+use crate::hex::{cube_coordinates, Cube, BOARD_RADIUS, DIRECTIONS};
+use crate::render::DIRECTION_NAMES;
+use crate::rules::{shadows, State};
+
+// Distance from the center of a hex to its corners, in pixels
+const HEX_SIZE: f64 = 32.0;
+const HEADER: f64 = 50.0;
+const FOOTER: f64 = 40.0;
+
+const RICHNESS_COLORS: [&str; 4] = ["#555555", "#d9c27a", "#9ccc65", "#4caf50"];
+const OWNER_COLORS: [&str; 2] = ["#1e88e5", "#e53935"];
+const TREE_RADIUS: [f64; 4] = [4.0, 8.0, 12.0, 16.0];
+const HIGHLIGHT: &str = "#ffd600";
+
+fn width() -> f64 {
+    2.0 * (BOARD_RADIUS as f64 * 3f64.sqrt() + 1.0) * HEX_SIZE
+}
+
+fn height() -> f64 {
+    2.0 * (BOARD_RADIUS as f64 * 1.5 + 1.0) * HEX_SIZE + HEADER + FOOTER
+}
+
+// Center of a pointy topped hex, rows going down the page
+fn center(cube: Cube) -> (f64, f64) {
+    let x = 3f64.sqrt() * HEX_SIZE * (cube.0 as f64 + cube.2 as f64 / 2.0);
+    let y = 1.5 * HEX_SIZE * cube.2 as f64;
+    (x + width() / 2.0, y + HEADER + (height() - HEADER - FOOTER) / 2.0)
+}
+
+fn hexagon(center: (f64, f64)) -> String {
+    let corners: Vec<String> = (0..6)
+        .map(|corner| {
+            let angle = (60.0 * corner as f64 - 30.0).to_radians();
+            format!("{:.1},{:.1}", center.0 + HEX_SIZE * angle.cos(), center.1 + HEX_SIZE * angle.sin())
+        })
+        .collect();
+    corners.join(" ")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+// Opening tag, background and the arrow head of the seed and sun arrows
+fn header(svg: &mut String) {
+    writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w:.0}" height="{h:.0}" viewBox="0 0 {w:.0} {h:.0}" font-family="sans-serif">"#, w = width(), h = height()).unwrap();
+    let head = format!(r#"<path d="M0,0 L10,5 L0,10 z" fill="{}"/>"#, HIGHLIGHT);
+    writeln!(svg, r#"<defs><marker id="arrow" viewBox="0 0 10 10" refX="9" refY="5" markerWidth="6" markerHeight="6" orient="auto">{}</marker></defs>"#, head).unwrap();
+    writeln!(svg, r##"<rect width="100%" height="100%" fill="#263238"/>"##).unwrap();
+}
+
+fn text(svg: &mut String, position: (f64, f64), size: i32, color: &str, content: &str) {
+    writeln!(svg, r#"<text x="{:.1}" y="{:.1}" font-size="{}" fill="{}">{}</text>"#, position.0, position.1, size, color, content).unwrap();
+}
+
+fn arrow(svg: &mut String, from: (f64, f64), to: (f64, f64)) {
+    let line = format!(r#"x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}""#, from.0, from.1, to.0, to.1);
+    writeln!(svg, r#"<line {} stroke="{}" stroke-width="3" marker-end="url(#arrow)"/>"#, line, HIGHLIGHT).unwrap();
+}
+
+// Day, nutrients, both players' sun and score, and an arrow in the
+// direction of today's shadows
+fn status(svg: &mut String, state: &State) {
+    let direction = (state.day % 6) as usize;
+    let day = format!("Day {}  nutrients {}  shadows {}", state.day, state.nutrients, DIRECTION_NAMES[direction]);
+    text(svg, (10.0, 20.0), 14, "#eceff1", &day);
+
+    let player = |i: usize| format!(r#"<tspan fill="{}">sun {} score {}</tspan>"#, OWNER_COLORS[i], state.sun[i], state.score[i]);
+    text(svg, (10.0, 40.0), 14, "#eceff1", &format!("{}  {}", player(0), player(1)));
+
+    let (from, to) = (center((0, 0, 0)), center(DIRECTIONS[direction]));
+    let half = ((to.0 - from.0) / 3.0, (to.1 - from.1) / 3.0);
+    let middle = (width() - 30.0, 25.0);
+    arrow(svg, (middle.0 - half.0, middle.1 - half.1), (middle.0 + half.0, middle.1 + half.1));
+}
+
+// The hex, shaded or not, and its index
+fn hex_cell(svg: &mut String, position: (f64, f64), index: i32, richness: usize, shade: Option<i32>) {
+    writeln!(svg, r##"<polygon points="{}" fill="{}" stroke="#263238" stroke-width="2"/>"##, hexagon(position), RICHNESS_COLORS[richness]).unwrap();
+    if let Some(size) = shade {
+        shadow(svg, position, size);
+    }
+    text(svg, (position.0 - 20.0, position.1 - 14.0), 9, "#263238", &index.to_string());
+}
+
+// Darker for bigger shadows, with their size
+fn shadow(svg: &mut String, position: (f64, f64), size: i32) {
+    writeln!(svg, r#"<polygon points="{}" fill="black" fill-opacity="{:.2}"/>"#, hexagon(position), 0.15 * size as f64).unwrap();
+    text(svg, (position.0 + 8.0, position.1 + 22.0), 10, "#eceff1", &format!("~{}", size));
+}
+
+fn tree(svg: &mut String, position: (f64, f64), tree: &Tree) {
+    let color = OWNER_COLORS[if tree.is_mine { 0 } else { 1 }];
+    let dash = if tree.is_dormant { r#" stroke-dasharray="3,2""# } else { "" };
+    let disc = format!(r#"cx="{:.1}" cy="{:.1}" r="{:.0}""#, position.0, position.1, TREE_RADIUS[tree.size as usize]);
+    writeln!(svg, r#"<circle {} fill="{}" stroke="white" stroke-width="1.5"{}/>"#, disc, color, dash).unwrap();
+    if tree.is_dormant {
+        let (x, y) = (position.0 + 12.0, position.1 - 8.0);
+        writeln!(svg, r#"<text x="{:.1}" y="{:.1}" font-size="11" font-style="italic" fill="white">z</text>"#, x, y).unwrap();
+    }
+}
+
+fn ring(svg: &mut String, position: (f64, f64)) {
+    let circle = format!(r#"cx="{:.1}" cy="{:.1}" r="{:.0}""#, position.0, position.1, HEX_SIZE * 0.75);
+    writeln!(svg, r#"<circle {} fill="none" stroke="{}" stroke-width="3"/>"#, circle, HIGHLIGHT).unwrap();
+}
+
+// The action in bold followed by the bot's message
+fn caption(svg: &mut String, decision: &Decision) {
+    let action = format!(r#"<tspan fill="{}" font-weight="bold">{}</tspan>"#, HIGHLIGHT, decision.action);
+    text(svg, (10.0, height() - 15.0), 12, "#eceff1", &format!("{} {}", action, escape(&decision.message)));
+}
+
+pub fn render_svg(state: &State, board: &Area, decision: Option<&Decision>, show_shadows: bool) -> String {
+    let coordinates = cube_coordinates(board);
+    let tomorrow = state.day + 1;
+    let shaded = if show_shadows { shadows(board, &state.forest, tomorrow) } else { Default::default() };
+
+    let mut svg = String::new();
+    header(&mut svg);
+    status(&mut svg, state);
+
+    let mut cells: Vec<_> = coordinates.iter().map(|(index, cube)| (*index, center(*cube))).collect();
+    cells.sort_by_key(|(index, _)| *index);
+    for &(index, position) in &cells {
+        hex_cell(&mut svg, position, index, board[&index].richness as usize, shaded.get(&index).copied());
+    }
+    for &(index, position) in &cells {
+        if let Some(found) = state.forest.get(&index) {
+            tree(&mut svg, position, found);
+        }
+    }
+
+    if let Some(decision) = decision {
+        let position = |cell: i32| coordinates.get(&cell).map(|cube| center(*cube));
+        match decision.action {
+            Action::Grow(cell) | Action::Complete(cell) => {
+                if let Some(at) = position(cell) {
+                    ring(&mut svg, at);
+                }
+            }
+            Action::Seed(target, origin) => {
+                if let (Some(from), Some(to)) = (position(origin), position(target)) {
+                    arrow(&mut svg, from, to);
+                }
+                if let Some(at) = position(target) {
+                    ring(&mut svg, at);
+                }
+            }
+            Action::Wait | Action::Null => (),
+        }
+        caption(&mut svg, decision);
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapgen::build_board;
+    use crate::test_util::position;
+
+    #[test]
+    fn draws_dormant_trees_shadows_and_the_escaped_message() {
+        let board = build_board(&[]);
+        let state = position(0).sun([2, 2]).trees(&[(0, 3, true), (4, 1, false)]).dormant(&[0]).state();
+        let decision = Decision::new(Action::Seed(13, 4), "a<b & c>d".to_string());
+
+        let svg = render_svg(&state, &board, Some(&decision), true);
+        assert!(svg.starts_with("<svg ") && svg.ends_with("</svg>\n"));
+        // Only the tree on the center is dormant
+        assert_eq!(svg.matches(r#"stroke-dasharray="3,2""#).count(), 1);
+        assert_eq!(svg.matches(">z</text>").count(), 1);
+        // Tomorrow's shadows go north east: 3 cells from the center, 1 from cell 4
+        assert_eq!(svg.matches(r#"fill="black" fill-opacity="0.45""#).count(), 3);
+        assert_eq!(svg.matches(r#"fill="black" fill-opacity="0.15""#).count(), 1);
+        assert_eq!(svg.matches(">~3</text>").count(), 3);
+        assert!(svg.contains("a&lt;b &amp; c&gt;d"));
+        assert!(!svg.contains("a<b"));
+        assert!(svg.contains(">SEED 4 13</tspan>"));
+    }
+}
@@ -0,0 +1,56 @@
+//! Positions for the unit tests, described the way the referee's input
+//! gives them: a game context and a forest.
+
+use crate::game::{Forest, GameContext, Tree};
+use crate::rules::State;
+
+#[derive(Clone)]
+pub(crate) struct Position {
+    pub(crate) context: GameContext,
+    pub(crate) forest: Forest,
+}
+
+// Position on the given day with 20 nutrients, no sun, no score and no tree
+pub(crate) fn position(day: i32) -> Position {
+    let context = GameContext { day, nutrients: 20, sun: 0, score: 0, op_sun: 0, op_score: 0, op_is_waiting: false };
+    Position { context, forest: Forest::default() }
+}
+
+impl Position {
+    pub(crate) fn nutrients(mut self, nutrients: i32) -> Position {
+        self.context.nutrients = nutrients;
+        self
+    }
+
+    // Ours first, then the opponent's
+    pub(crate) fn sun(mut self, sun: [i32; 2]) -> Position {
+        self.context.sun = sun[0];
+        self.context.op_sun = sun[1];
+        self
+    }
+
+    pub(crate) fn opponent_waiting(mut self) -> Position {
+        self.context.op_is_waiting = true;
+        self
+    }
+
+    // Trees as (cell, size, is_mine), awake
+    pub(crate) fn trees(mut self, trees: &[(i32, i32, bool)]) -> Position {
+        for &(cell_index, size, is_mine) in trees {
+            self.forest.insert(cell_index, Tree { cell_index, size, is_mine, is_dormant: false });
+        }
+        self
+    }
+
+    // Puts the trees on these cells to sleep
+    pub(crate) fn dormant(mut self, cells: &[i32]) -> Position {
+        for cell_index in cells {
+            self.forest.get_mut(cell_index).unwrap().is_dormant = true;
+        }
+        self
+    }
+
+    pub(crate) fn state(&self) -> State {
+        State::new(&self.context, &self.forest)
+    }
+}
@@ -0,0 +1,141 @@
+//! Time keeping for the per-turn response limits of the game.
+
+use std::time::{Duration, Instant};
+
+#[derive(Copy, Clone)]
+pub struct Deadline {
+    at: Instant,
+    // replaces the clock, so that a seeded search does the same work every run
+    max_iterations: Option<i32>,
+}
+
+impl Deadline {
+    pub fn new(at: Instant) -> Deadline {
+        Deadline { at, max_iterations: None }
+    }
+
+    pub fn after(duration: Duration) -> Deadline {
+        Deadline::new(Instant::now() + duration)
+    }
+
+    pub fn with_max_iterations(self, max_iterations: Option<i32>) -> Deadline {
+        Deadline { max_iterations, ..self }
+    }
+
+    pub fn reached(&self, iterations: i32) -> bool {
+        match self.max_iterations {
+            Some(max_iterations) => iterations >= max_iterations,
+            None => Instant::now() >= self.at,
+        }
+    }
+
+    // Deadline after the given share of what is left, `iterations` being the
+    // work already done
+    pub fn share(&self, iterations: i32, share: f64) -> Deadline {
+        Deadline {
+            at: Instant::now() + self.remaining().mul_f64(share),
+            max_iterations: self.max_iterations.map(|max| iterations + ((max - iterations) as f64 * share).ceil() as i32),
+        }
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.at.saturating_duration_since(Instant::now())
+    }
+}
+
+pub struct TimeManager {
+    // CodinGame allows 1000 ms to answer the first turn and 100 ms afterwards
+    pub first_turn_limit: Duration,
+    pub turn_limit: Duration,
+    // kept free for reading input, printing and timer jitter
+    pub safety_margin: Duration,
+    // days from which the margin is cut to critical_safety_margin, as
+    // harvesting decides the game. No time is saved on other days: the limit
+    // is per turn and nothing left unused carries over.
+    pub critical_from_day: i32,
+    pub critical_safety_margin: Duration,
+    first_turn: bool,
+    turn_start: Option<Instant>,
+    pub overruns: u32,
+}
+
+impl Default for TimeManager {
+    fn default() -> Self {
+        TimeManager {
+            first_turn_limit: Duration::from_millis(1000),
+            turn_limit: Duration::from_millis(100),
+            safety_margin: Duration::from_millis(5),
+            critical_from_day: 20,
+            critical_safety_margin: Duration::from_millis(3),
+            first_turn: true,
+            turn_start: None,
+            overruns: 0,
+        }
+    }
+}
+
+impl TimeManager {
+    pub fn is_critical(&self, day: i32) -> bool {
+        day >= self.critical_from_day
+    }
+
+    // Time to think on the next turn: all of the limit but the safety margin
+    // of the day
+    pub fn budget(&self, day: i32) -> Duration {
+        let margin = if self.is_critical(day) { self.critical_safety_margin } else { self.safety_margin };
+        self.limit().saturating_sub(margin)
+    }
+
+    // Call as soon as the first line of the turn has been read
+    pub fn start_turn(&mut self, day: i32) -> Deadline {
+        let start = Instant::now();
+        self.turn_start = Some(start);
+        Deadline::new(start + self.budget(day))
+    }
+
+    // Call once the action is printed. Returns how long the turn took and
+    // reports on stderr when it went over the limit.
+    pub fn end_turn(&mut self) -> Duration {
+        let elapsed = self.turn_start.map_or(Duration::ZERO, |start| start.elapsed());
+        let limit = self.limit();
+        if elapsed > limit {
+            self.overruns += 1;
+            eprintln!("Turn overran: {} ms (limit {} ms, {} overruns)", elapsed.as_millis(), limit.as_millis(), self.overruns);
+        }
+        self.first_turn = false;
+        elapsed
+    }
+
+    fn limit(&self) -> Duration {
+        if self.first_turn { self.first_turn_limit } else { self.turn_limit }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budget_by_turn() {
+        let mut time_manager = TimeManager::default();
+        assert_eq!(time_manager.budget(0), Duration::from_millis(995));
+
+        time_manager.start_turn(0);
+        time_manager.end_turn();
+        assert_eq!(time_manager.budget(1), Duration::from_millis(95));
+        assert_eq!(time_manager.budget(19), Duration::from_millis(95));
+        assert_eq!(time_manager.budget(20), Duration::from_millis(97));
+        assert_eq!(time_manager.budget(23), Duration::from_millis(97));
+    }
+
+    #[test]
+    fn iteration_budget_shares() {
+        let deadline = Deadline::after(Duration::from_secs(60)).with_max_iterations(Some(1000));
+        assert!(!deadline.reached(999));
+        assert!(deadline.reached(1000));
+
+        let half = deadline.share(200, 0.5);
+        assert!(!half.reached(599));
+        assert!(half.reached(600));
+    }
+}
@@ -0,0 +1,208 @@
+//! Reconstruction of the opponent's actions by comparing the positions of
+//! two consecutive turns: their sun, score, waiting flag and trees.
+//!
+//! Between two of our turns the opponent acts once, or several times if we
+//! were waiting, always within the same day. New seeds, grown and removed
+//! trees tell what they did, a big tree replaced by a seed being a COMPLETE
+//! followed by a SEED on the freed cell. A seed's origin is the tree that
+//! went dormant, or the closest tree in range when the day changed and
+//! dormancy was reset.
+//!
+//! When our own seed left no tree, the opponent seeded the same cell and
+//! both seeds were cancelled: their sun spent shows no charge for it.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::game::{Action, Area, Forest};
+use crate::hex::{cube_coordinates, distance, Cube};
+use crate::rules::{get_my_sun_points, State};
+
+pub struct ObservedTurn {
+    // day the actions were played on
+    pub day: i32,
+    pub actions: Vec<Action>,
+    pub waited: bool,
+    // whether they had a tree to complete and the sun for it
+    pub could_complete: bool,
+    pub sun_spent: i32,
+    pub points: i32,
+}
+
+impl fmt::Display for ObservedTurn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let actions: Vec<String> = self.actions.iter().map(|a| a.to_string()).collect();
+        write!(f, "day {}: [{}]{} sun spent: {}, points: {}", self.day, actions.join(", "), if self.waited { " WAIT" } else { "" }, self.sun_spent, self.points)
+    }
+}
+
+#[derive(Default)]
+pub struct OpponentTracker {
+    previous: Option<State>,
+    // our action since the previous turn
+    played: Option<Action>,
+    coordinates: HashMap<i32, Cube>,
+    pub history: Vec<ObservedTurn>,
+}
+
+impl OpponentTracker {
+    // Call with the action we answer each turn with
+    pub fn played(&mut self, action: Action) {
+        self.played = Some(action);
+    }
+
+    // Compares with the previous turn and returns what the opponent did in
+    // between, unless they were asleep all along
+    pub fn observe(&mut self, state: &State, board: &Area) -> Option<&ObservedTurn> {
+        if self.coordinates.is_empty() {
+            self.coordinates = cube_coordinates(board);
+        }
+
+        let played = self.played.take();
+        let previous = self.previous.replace(state.clone())?;
+        let previous_forest = &previous.forest;
+        let forest = &state.forest;
+
+        // Asleep since the previous turn, the opponent could not act
+        if previous.waiting[1] {
+            return None;
+        }
+
+        let new_day = state.day != previous.day;
+        let mut actions = vec![];
+
+        for (cell_index, before) in previous_forest.iter().filter(|(_, tree)| !tree.is_mine) {
+            match forest.get(cell_index) {
+                None => actions.push(Action::Complete(*cell_index)),
+                Some(after) if after.size > before.size => actions.push(Action::Grow(*cell_index)),
+                Some(after) if after.size < before.size => actions.push(Action::Complete(*cell_index)),
+                _ => (),
+            }
+        }
+
+        // Seeds on empty cells, and on the cells of trees they completed
+        for (cell_index, _) in forest.iter().filter(|(id, tree)| !tree.is_mine && previous_forest.get(id).map_or(true, |before| before.size > tree.size)) {
+            let origin = self.seed_origin(*cell_index, previous_forest, forest, new_day);
+            actions.push(Action::Seed(*cell_index, origin));
+        }
+
+        if let Some(Action::Seed(target, _)) = played {
+            if !forest.get(&target).map_or(false, |tree| tree.is_mine) {
+                let origin = self.seed_origin(target, previous_forest, forest, new_day);
+                actions.push(Action::Seed(target, origin));
+            }
+        }
+        actions.sort();
+
+        // Sun of a new day is gathered before the turn starts
+        let gathered = if new_day { get_my_sun_points(board, &state.day, forest, 1) } else { 0 };
+        let waited = new_day || state.waiting[1];
+
+        let could_complete = previous.sun[1] >= 4 && previous_forest.values().any(|tree| !tree.is_mine && tree.size == 3 && !tree.is_dormant);
+
+        self.history.push(ObservedTurn {
+            day: previous.day,
+            actions,
+            waited,
+            could_complete,
+            sun_spent: previous.sun[1] + gathered - state.sun[1],
+            points: state.score[1] - previous.score[1],
+        });
+        self.history.last()
+    }
+
+    // Falls back to the target itself when no tree could have planted it. The
+    // parent is still there at the same size, neither completed nor grown.
+    fn seed_origin(&self, target: i32, previous_forest: &Forest, forest: &Forest, new_day: bool) -> i32 {
+        let target_cube = self.coordinates[&target];
+        previous_forest
+            .values()
+            .filter(|tree| !tree.is_mine && tree.cell_index != target && tree.size >= distance(self.coordinates[&tree.cell_index], target_cube))
+            .filter(|tree| match forest.get(&tree.cell_index) {
+                Some(after) if !after.is_mine && after.size == tree.size => new_day || (!tree.is_dormant && after.is_dormant),
+                _ => false,
+            })
+            .min_by_key(|tree| (distance(self.coordinates[&tree.cell_index], target_cube), tree.cell_index))
+            .map_or(target, |tree| tree.cell_index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapgen::build_board;
+    use crate::test_util::{position, Position};
+
+    // Position on `day` where we have 10 sun and the opponent `op_sun`
+    fn turn(day: i32, op_sun: i32) -> Position {
+        position(day).sun([10, op_sun])
+    }
+
+    // What the tracker makes of the turn from `before` to `after`
+    fn observe(before: &Position, after: &Position, played: Action) -> (Vec<Action>, i32) {
+        let board = build_board(&[]);
+        let mut tracker = OpponentTracker::default();
+        tracker.observe(&before.state(), &board);
+        tracker.played(played);
+        let observed = tracker.observe(&after.state(), &board).unwrap();
+        (observed.actions.clone(), observed.sun_spent)
+    }
+
+    #[test]
+    fn grow_and_seed_on_the_same_day() {
+        let before = turn(3, 10).trees(&[(0, 1, true), (4, 1, false), (5, 2, false)]);
+        let after = turn(3, 7).trees(&[(0, 1, true), (4, 2, false), (5, 2, false), (16, 0, false)]).dormant(&[0, 4, 5, 16]);
+
+        let (actions, sun_spent) = observe(&before, &after, Action::Grow(0));
+
+        assert_eq!(actions, vec![Action::Grow(4), Action::Seed(16, 5)]);
+        assert_eq!(sun_spent, 3);
+    }
+
+    #[test]
+    fn complete_then_seed_on_the_same_cell() {
+        let before = turn(15, 10).trees(&[(0, 1, true), (4, 3, false), (5, 2, false)]).dormant(&[0]);
+        let after = turn(15, 6).trees(&[(0, 1, true), (4, 0, false), (5, 2, false)]).dormant(&[0, 4, 5]);
+
+        // 4 sun for the COMPLETE, the seed is free without other seeds
+        let (actions, sun_spent) = observe(&before, &after, Action::Wait);
+
+        assert_eq!(actions, vec![Action::Seed(4, 5), Action::Complete(4)]);
+        assert_eq!(sun_spent, 4);
+    }
+
+    #[test]
+    fn seed_conflict_leaves_no_tree() {
+        // Both seed cell 2, between our tree on 1 and theirs on 3
+        let before = turn(6, 5).trees(&[(1, 1, true), (3, 1, false), (7, 0, true), (10, 0, false)]);
+        let after = turn(6, 5).trees(&[(1, 1, true), (3, 1, false), (7, 0, true), (10, 0, false)]).dormant(&[1, 3]);
+
+        let (actions, sun_spent) = observe(&before, &after, Action::Seed(2, 1));
+
+        assert_eq!(actions, vec![Action::Seed(2, 3)]);
+        assert_eq!(sun_spent, 0);
+    }
+
+    #[test]
+    fn our_seed_planted_is_no_conflict() {
+        let before = turn(6, 5).trees(&[(1, 1, true), (3, 1, false)]);
+        let after = turn(6, 5).opponent_waiting().trees(&[(1, 1, true), (2, 0, true), (3, 1, false)]).dormant(&[1, 2]);
+
+        let (actions, _) = observe(&before, &after, Action::Seed(2, 1));
+
+        assert!(actions.is_empty());
+    }
+
+    #[test]
+    fn actions_before_a_new_day() {
+        let before = turn(8, 10).trees(&[(0, 2, true), (4, 1, false), (13, 2, false)]);
+        let after = turn(9, 7).trees(&[(0, 2, true), (4, 2, false), (13, 2, false), (12, 0, false)]);
+
+        // Grown for 3 and seeded for free, both trees then shaded from the
+        // east on day 9
+        let (actions, sun_spent) = observe(&before, &after, Action::Wait);
+
+        assert_eq!(actions, vec![Action::Grow(4), Action::Seed(12, 13)]);
+        assert_eq!(sun_spent, 3);
+    }
+}
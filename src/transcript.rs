@@ -0,0 +1,38 @@
+//! Games recorded as the input one bot received, as the arena writes with
+//! `--log` or as copied from the standard input of a bot: the area, then for
+//! every turn the context, the forest and the legal actions.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use crate::game::{read_actionlist, read_area, read_forest, read_game_context, ActionList, Area};
+use crate::rules::State;
+
+pub struct Turn {
+    pub state: State,
+    pub actions: ActionList,
+}
+
+pub struct Transcript {
+    pub board: Area,
+    pub turns: Vec<Turn>,
+}
+
+impl Transcript {
+    pub fn read<R: BufRead>(input: &mut R) -> Transcript {
+        let board = read_area(input);
+        let mut turns = vec![];
+        while !input.fill_buf().unwrap().is_empty() {
+            let context = read_game_context(input);
+            let forest = read_forest(input);
+            let actions = read_actionlist(input);
+            turns.push(Turn { state: State::new(&context, &forest), actions });
+        }
+        Transcript { board, turns }
+    }
+
+    pub fn open(path: &str) -> Transcript {
+        let file = File::open(path).unwrap_or_else(|e| panic!("Failed to open {}: {}", path, e));
+        Transcript::read(&mut BufReader::new(file))
+    }
+}
@@ -0,0 +1,437 @@
+//! Decoupled UCT for the simultaneous turns of the game.
+//!
+//! Every node keeps separate bandit statistics for our actions and for the
+//! opponent's. Both players select independently from their own statistics
+//! and the joint action is resolved by the referee, so neither side gets to
+//! see the other's choice.
+//!
+//! Actions are introduced in the order of their heuristic score and only the
+//! first `widening_base * (visits + 1) ^ widening_exponent` of them are
+//! considered at a node (progressive widening), so the dozens of seed
+//! targets available early on do not dilute the search.
+//!
+//! Arms also keep RAVE (all moves as first) statistics, crediting an action
+//! whenever the same player plays it later in the simulation. They are
+//! blended with the UCT mean with weight sqrt(k / (3 visits + k)), k being
+//! `rave_equivalence`, which helps in the short time of a turn.
+//!
+//! The move played is picked from the statistics of our root arms with the
+//! same final move policies as flat Monte Carlo, the most visited by default.
+//!
+//! The tree is kept between turns: once the new position is known, the node
+//! reached by our played action and the opponent's answer becomes the root.
+
+use std::collections::HashSet;
+
+use rand::Rng;
+
+use crate::config::{flag, parsed_setting};
+use crate::game::{Action, Area, FixedState};
+use crate::heuristics::calculate_game_points_from_action;
+use crate::rollout::{root_actions, seeds_allowed, PlayoutConfig, Rollout};
+use crate::rules::{apply_joint_actions, possible_actions, State};
+use crate::stats::{print_statistics, ActionStats, FinalMovePolicy};
+use crate::time::Deadline;
+use crate::weights::EvalWeights;
+
+pub struct UctConfig {
+    pub exploration: f64,
+    // point difference that maps to a reward of about 0.76 (tanh(1))
+    pub reward_scale: f64,
+    pub widening_base: f64,
+    pub widening_exponent: f64,
+    // visits at which the UCT and RAVE means weigh about the same, 0 disables RAVE
+    pub rave_equivalence: f64,
+    pub playout: PlayoutConfig,
+    pub final_move: FinalMovePolicy,
+    // dumps the statistics of our root arms to stderr every turn
+    pub debug: bool,
+}
+
+impl Default for UctConfig {
+    fn default() -> Self {
+        UctConfig {
+            exploration: 0.7,
+            reward_scale: 20.0,
+            widening_base: 2.0,
+            widening_exponent: 0.5,
+            rave_equivalence: 200.0,
+            playout: PlayoutConfig::default(),
+            final_move: FinalMovePolicy::MaxVisits,
+            debug: false,
+        }
+    }
+}
+
+impl UctConfig {
+    // Defaults overridden by the settings of the same names, the playout
+    // settings, `final-move` and `debug`
+    pub fn from_settings() -> UctConfig {
+        let default = UctConfig::default();
+        UctConfig {
+            exploration: parsed_setting("exploration").unwrap_or(default.exploration),
+            reward_scale: parsed_setting("reward-scale").unwrap_or(default.reward_scale),
+            widening_base: parsed_setting("widening-base").unwrap_or(default.widening_base),
+            widening_exponent: parsed_setting("widening-exponent").unwrap_or(default.widening_exponent),
+            rave_equivalence: parsed_setting("rave-equivalence").unwrap_or(default.rave_equivalence),
+            playout: PlayoutConfig::from_settings(),
+            final_move: parsed_setting("final-move").unwrap_or(default.final_move),
+            debug: flag("debug"),
+        }
+    }
+}
+
+struct Arm {
+    action: Action,
+    visits: u32,
+    total: f64,
+    sum_squares: f64,
+    amaf_visits: u32,
+    amaf_total: f64,
+}
+
+impl Arm {
+    fn new(action: Action) -> Arm {
+        Arm { action, visits: 0, total: 0.0, sum_squares: 0.0, amaf_visits: 0, amaf_total: 0.0 }
+    }
+
+    fn mean(&self) -> f64 {
+        if self.visits == 0 { 0.0 } else { self.total / self.visits as f64 }
+    }
+
+    fn stats(&self) -> ActionStats {
+        ActionStats::from_sums(self.visits, self.total, self.sum_squares)
+    }
+
+    fn blended_mean(&self, rave_equivalence: f64) -> f64 {
+        if self.amaf_visits == 0 || rave_equivalence <= 0.0 {
+            return self.mean();
+        }
+        let beta = (rave_equivalence / (3.0 * self.visits as f64 + rave_equivalence)).sqrt();
+        (1.0 - beta) * self.mean() + beta * self.amaf_total / self.amaf_visits as f64
+    }
+}
+
+#[derive(Default)]
+struct Node {
+    visits: u32,
+    // arms[0] are our actions, arms[1] the opponent's
+    arms: [Vec<Arm>; 2],
+    // keyed by joint_code
+    children: Vec<(u32, usize)>,
+}
+
+// Arms for the actions of `player`, best heuristic score first
+fn ordered_arms(mut actions: Vec<Action>, state: &State, board: &Area, player: usize, weights: &EvalWeights) -> Vec<Arm> {
+    actions.sort_by_cached_key(|action| -calculate_game_points_from_action(action, state.nutrients, board, &state.forest, state.day, player, weights));
+    actions.into_iter().map(Arm::new).collect()
+}
+
+fn joint_code(actions: [Action; 2]) -> u32 {
+    (actions[0].code() as u32) << 16 | actions[1].code() as u32
+}
+
+fn joint_actions(code: u32) -> [Action; 2] {
+    [Action::from_code((code >> 16) as u16), Action::from_code(code as u16)]
+}
+
+pub struct SearchResult {
+    pub action: Action,
+    pub value: f64,
+    pub visits: u32,
+    pub choices: usize,
+    pub iterations: i32,
+    // visits of the root carried over from the previous turn
+    pub reused: u32,
+}
+
+pub struct Uct {
+    pub config: UctConfig,
+    nodes: Vec<Node>,
+    // position at nodes[0] and the action we played from it
+    root_state: Option<State>,
+    played: Option<Action>,
+}
+
+impl Uct {
+    pub fn new(config: UctConfig) -> Uct {
+        Uct { config, nodes: vec![], root_state: None, played: None }
+    }
+
+    pub fn search<R: Rng>(&mut self, root: &State, board: &Area, expected_actions: &[Action], deadline: &Deadline, rng: &mut R) -> SearchResult {
+        let root = self.advance(root, board);
+        let reused = self.nodes[0].visits;
+
+        // Keep the statistics of actions still legal, the referee has the last word
+        let mut old_arms = std::mem::take(&mut self.nodes[0].arms[0]);
+        self.nodes[0].arms[0] = ordered_arms(root_actions(&root, board, expected_actions, &self.config.playout.weights), &root, board, 0, &self.config.playout.weights)
+            .into_iter()
+            .map(|new_arm| match old_arms.iter().position(|arm| arm.action == new_arm.action) {
+                Some(i) => old_arms.swap_remove(i),
+                None => new_arm,
+            })
+            .collect();
+        if self.nodes[0].arms[1].is_empty() {
+            self.nodes[0].arms[1] = ordered_arms(possible_actions(&root, board, 1, seeds_allowed(&root, 1, &self.config.playout.weights)), &root, board, 1, &self.config.playout.weights);
+        }
+
+        let last_day = self.config.playout.last_day(root.day);
+
+        let mut iterations = 0;
+        while !deadline.reached(iterations) {
+            self.iterate(&root, board, rng, last_day);
+            iterations += 1;
+        }
+
+        let arms = &self.nodes[0].arms[0];
+        if self.config.debug {
+            print_statistics(arms.iter().filter(|arm| arm.visits > 0).map(|arm| (arm.action, arm.stats())).collect());
+        }
+        // Arms never tried only when nothing else is left
+        let value = |arm: &Arm| (arm.visits > 0, arm.stats().value(self.config.final_move));
+        let best = arms.iter().max_by(|a, b| value(a).partial_cmp(&value(b)).unwrap()).unwrap();
+        self.played = Some(best.action);
+        let result = SearchResult { action: best.action, value: best.mean(), visits: best.visits, choices: arms.len(), iterations, reused };
+        self.root_state = Some(root);
+        result
+    }
+
+    // Keeps the tree in step when our move was decided without a search, by
+    // the book or the endgame search, so that the next search still finds
+    // the subtree it should reuse
+    pub fn follow(&mut self, state: &State, board: &Area, action: Action) {
+        if !self.root_state.as_ref().map_or(false, |root| root.same_position(state)) {
+            let root = self.advance(state, board);
+            self.root_state = Some(root);
+        }
+        self.played = Some(action);
+    }
+
+    // Moves the root to the node matching the observed position, or starts a
+    // fresh tree when it cannot be found. Returns the state of the new root.
+    fn advance(&mut self, observed: &State, board: &Area) -> State {
+        let found = match (&self.root_state, self.played) {
+            (Some(root_state), Some(played)) => self.find_descendant(root_state, played, observed, board),
+            _ => None,
+        };
+
+        match found {
+            Some((node, state)) => {
+                self.keep_subtree(node);
+                state
+            }
+            None => {
+                self.nodes.clear();
+                self.nodes.push(Node::default());
+                observed.clone()
+            }
+        }
+    }
+
+    // Between two of our turns we play one action, then the opponent may keep
+    // acting while we wait, so the new position can be several plies down.
+    fn find_descendant(&self, root_state: &State, played: Action, observed: &State, board: &Area) -> Option<(usize, State)> {
+        let mut frontier = vec![(0, root_state.clone())];
+
+        while let Some((node, state)) = frontier.pop() {
+            let our_action = if node == 0 { played } else { Action::Wait };
+            for (code, child) in &self.nodes[node].children {
+                let actions = joint_actions(*code);
+                if actions[0] != our_action {
+                    continue;
+                }
+                let mut next = state.clone();
+                apply_joint_actions(&mut next, board, actions);
+                if next.same_position(observed) {
+                    return Some((*child, next));
+                }
+                if next.waiting[0] && next.day == observed.day {
+                    frontier.push((*child, next));
+                }
+            }
+        }
+        None
+    }
+
+    // Drops everything outside the subtree of `new_root`, which becomes nodes[0]
+    fn keep_subtree(&mut self, new_root: usize) {
+        let mut old_nodes = std::mem::take(&mut self.nodes);
+
+        let mut mapping = vec![usize::MAX; old_nodes.len()];
+        let mut order = vec![new_root];
+        mapping[new_root] = 0;
+        let mut i = 0;
+        while i < order.len() {
+            for (_, child) in &old_nodes[order[i]].children {
+                mapping[*child] = order.len();
+                order.push(*child);
+            }
+            i += 1;
+        }
+
+        for old in order {
+            let mut node = std::mem::take(&mut old_nodes[old]);
+            for (_, child) in node.children.iter_mut() {
+                *child = mapping[*child];
+            }
+            self.nodes.push(node);
+        }
+    }
+
+    fn iterate<R: Rng>(&mut self, root: &State, board: &Area, rng: &mut R, last_day: i32) {
+        let mut rollout = Rollout::recorded(root.clone());
+        let mut node = 0;
+        let mut path: Vec<(usize, [usize; 2])> = vec![];
+
+        // Selection and expansion of one new node
+        while !rollout.state.is_game_over() && rollout.state.day < last_day {
+            if self.nodes[node].arms[0].is_empty() {
+                let state = &rollout.state;
+                let weights = &self.config.playout.weights;
+                self.nodes[node].arms = [
+                    ordered_arms(possible_actions(state, board, 0, seeds_allowed(state, 0, weights)), state, board, 0, weights),
+                    ordered_arms(possible_actions(state, board, 1, seeds_allowed(state, 1, weights)), state, board, 1, weights),
+                ];
+            }
+
+            let choice = [self.select(node, 0), self.select(node, 1)];
+            let actions = [self.nodes[node].arms[0][choice[0]].action, self.nodes[node].arms[1][choice[1]].action];
+            path.push((node, choice));
+            rollout.step(board, &self.config.playout.weights, actions);
+
+            let code = joint_code(actions);
+            match self.nodes[node].children.iter().find(|(joint, _)| *joint == code) {
+                Some((_, child)) => node = *child,
+                None => {
+                    let child = self.nodes.len();
+                    self.nodes.push(Node::default());
+                    self.nodes[node].children.push((code, child));
+                    break;
+                }
+            }
+        }
+
+        rollout.play_out(board, &self.config.playout, rng, last_day);
+
+        let reward = ((rollout.points[0] - rollout.points[1]) as f64 / self.config.reward_scale).tanh();
+        let history = rollout.history.unwrap();
+
+        // Actions each player made from the current node down to the end of
+        // the simulation, starting with the rollout part
+        let mut played: [HashSet<u16, FixedState>; 2] = Default::default();
+        for actions in &history[path.len()..] {
+            for player in 0..2 {
+                played[player].insert(actions[player].code());
+            }
+        }
+
+        for (depth, (node, choice)) in path.into_iter().enumerate().rev() {
+            for player in 0..2 {
+                played[player].insert(history[depth][player].code());
+            }
+
+            let node = &mut self.nodes[node];
+            node.visits += 1;
+            for (player, sign) in [(0, 1.0), (1, -1.0)] {
+                let arm = &mut node.arms[player][choice[player]];
+                arm.visits += 1;
+                arm.total += sign * reward;
+                arm.sum_squares += reward * reward;
+
+                for arm in node.arms[player].iter_mut().filter(|arm| played[player].contains(&arm.action.code())) {
+                    arm.amaf_visits += 1;
+                    arm.amaf_total += sign * reward;
+                }
+            }
+        }
+    }
+
+    // UCB1 over the widened arms of one player, trying every action once first
+    fn select(&self, node: usize, player: usize) -> usize {
+        let node = &self.nodes[node];
+        let widened = (self.config.widening_base * ((node.visits + 1) as f64).powf(self.config.widening_exponent)).ceil() as usize;
+        let log_visits = (node.visits.max(1) as f64).ln();
+        let mut best = 0;
+        let mut best_value = f64::MIN;
+        for (i, arm) in node.arms[player].iter().take(widened.max(1)).enumerate() {
+            if arm.visits == 0 {
+                return i;
+            }
+            let value = arm.blended_mean(self.config.rave_equivalence) + self.config.exploration * (log_visits / arm.visits as f64).sqrt();
+            if value > best_value {
+                best = i;
+                best_value = value;
+            }
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::mapgen::build_board;
+    use crate::rng::Xorshift;
+    use crate::rules::legal_actions;
+    use crate::test_util::position;
+
+    fn node(visits: u32, children: &[usize]) -> Node {
+        Node { visits, arms: Default::default(), children: children.iter().map(|&child| (child as u32, child)).collect() }
+    }
+
+    #[test]
+    fn keep_subtree_renumbers_the_nodes_kept() {
+        let mut uct = Uct::new(UctConfig::default());
+        // Visits tell the original index of every node
+        uct.nodes = vec![node(0, &[1, 2]), node(1, &[3]), node(2, &[4, 5]), node(3, &[]), node(4, &[6]), node(5, &[]), node(6, &[])];
+
+        uct.keep_subtree(2);
+
+        let visits: Vec<u32> = uct.nodes.iter().map(|node| node.visits).collect();
+        assert_eq!(visits, vec![2, 4, 5, 6]);
+        let children: Vec<Vec<usize>> = uct.nodes.iter().map(|node| node.children.iter().map(|(_, child)| *child).collect()).collect();
+        assert_eq!(children, vec![vec![1, 2], vec![3], vec![], vec![]]);
+        // The joint actions leading to the children are kept
+        assert_eq!(uct.nodes[0].children[1].0, 5);
+    }
+
+    #[test]
+    fn the_next_search_starts_from_the_position_reached() {
+        let board = build_board(&[]);
+        let state = position(5).sun([6, 6]).trees(&[(0, 2, true), (1, 1, true), (4, 2, false), (5, 1, false)]).state();
+        let actions = legal_actions(&state, &board, 0);
+        let mut uct = Uct::new(UctConfig::default());
+        let mut rng = Xorshift::new(1);
+        let deadline = Deadline::after(Duration::from_secs(10)).with_max_iterations(Some(500));
+
+        let result = uct.search(&state, &board, &actions, &deadline, &mut rng);
+
+        // The most visited answer of the opponent to the action we played
+        let (code, child) = *uct.nodes[0].children.iter().filter(|(code, _)| joint_actions(*code)[0] == result.action).max_by_key(|(_, child)| uct.nodes[*child].visits).unwrap();
+        let mut next = state.clone();
+        apply_joint_actions(&mut next, &board, joint_actions(code));
+        let child_visits = uct.nodes[child].visits;
+        let (found, found_state) = uct.find_descendant(&state, result.action, &next, &board).unwrap();
+        assert_eq!(found, child);
+        assert!(found_state.same_position(&next));
+
+        let next_actions = legal_actions(&next, &board, 0);
+        let deadline = Deadline::after(Duration::from_secs(10)).with_max_iterations(Some(0));
+        assert_eq!(uct.search(&next, &board, &next_actions, &deadline, &mut rng).reused, child_visits);
+    }
+
+    #[test]
+    fn following_a_move_decided_elsewhere() {
+        let board = build_board(&[]);
+        let state = position(5).sun([6, 6]).trees(&[(0, 2, true), (4, 2, false)]).state();
+        let mut uct = Uct::new(UctConfig::default());
+
+        uct.follow(&state, &board, Action::Grow(0));
+
+        assert!(uct.root_state.as_ref().unwrap().same_position(&state));
+        assert_eq!(uct.played, Some(Action::Grow(0)));
+        assert_eq!(uct.nodes.len(), 1);
+    }
+}
@@ -0,0 +1,216 @@
+//! Weights of the hand tuned evaluation: the action heuristics, the leaf
+//! evaluation, when seeding is considered and the horizon and temperature of
+//! the playouts. Defaults are the original values, the baked weights of
+//! weights_data.rs apply over them, and for experiments a file given with
+//! `--weights` or RUSTBOT_WEIGHTS applies over those.
+//!
+//! Weights files have one `name value` line per weight to change, blank
+//! lines and lines starting with `#` are ignored. Integer weights are
+//! rounded.
+
+use std::fmt;
+use std::fs;
+
+use crate::config::setting;
+use crate::weights_data::WEIGHTS;
+
+pub const WEIGHT_NAMES: [&str; 20] = [
+    "grow_seed_bonus",
+    "grow_late_day",
+    "grow_late_base",
+    "complete_late_day",
+    "complete_late_bonus",
+    "harvest_bonus_after_day",
+    "seed_empty_neighbor_penalty",
+    "seed_center_divisor",
+    "seed_richness",
+    "sun_as_points_until_day",
+    "tree_maturity_0",
+    "tree_maturity_1",
+    "tree_maturity_2",
+    "tree_maturity_3",
+    "sun_income_share",
+    "seed_max_trees",
+    "seed_until_day",
+    "greedy_complete_from_day",
+    "horizon",
+    "temperature",
+];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EvalWeights {
+    // points for growing a seed into a sprout
+    pub grow_seed_bonus: i32,
+    // from this day, growing scores grow_late_base + the tree's size
+    pub grow_late_day: i32,
+    pub grow_late_base: i32,
+    // from this day, completing scores complete_late_bonus
+    pub complete_late_day: i32,
+    pub complete_late_bonus: i32,
+    // after this day, completing also scores the harvest
+    pub harvest_bonus_after_day: i32,
+    // seeding loses this per empty neighbour of the target
+    pub seed_empty_neighbor_penalty: i32,
+    // seeding scores (36 - target) / seed_center_divisor, favouring the center
+    pub seed_center_divisor: i32,
+    // seeding scores the target's richness times this
+    pub seed_richness: i32,
+    // sun gathered before this day is also credited as points in rollouts
+    pub sun_as_points_until_day: i32,
+    // share of the harvest value credited to a tree by size, as smaller trees
+    // may never make it to a harvest
+    pub tree_maturity: [f64; 4],
+    // share of the sun income expected to survive the shadows
+    pub sun_income_share: f64,
+    // seeding is only considered below this many trees and before this day,
+    // rollouts stopping on a random day between the fifth and this one
+    pub seed_max_trees: i32,
+    pub seed_until_day: i32,
+    // first day the greedy bot completes a tree whose harvest is worth it
+    pub greedy_complete_from_day: i32,
+    // how many days ahead of the current one the rollouts are played, and the
+    // softmax temperature of their policy
+    pub horizon: i32,
+    pub temperature: f64,
+}
+
+impl Default for EvalWeights {
+    fn default() -> Self {
+        EvalWeights {
+            grow_seed_bonus: 10,
+            grow_late_day: 24,
+            grow_late_base: 5,
+            complete_late_day: 20,
+            complete_late_bonus: 10,
+            harvest_bonus_after_day: 15,
+            seed_empty_neighbor_penalty: 2,
+            seed_center_divisor: 10,
+            seed_richness: 1,
+            sun_as_points_until_day: 14,
+            tree_maturity: [0.3, 0.5, 0.75, 1.0],
+            sun_income_share: 0.5,
+            seed_max_trees: 8,
+            seed_until_day: 14,
+            greedy_complete_from_day: 14,
+            horizon: 10,
+            temperature: 3.0,
+        }
+    }
+}
+
+impl EvalWeights {
+    // Defaults with the weights baked into the bot
+    pub fn baked() -> EvalWeights {
+        let mut weights = EvalWeights::default();
+        weights.apply(WEIGHTS).unwrap_or_else(|e| panic!("Invalid baked weights: {}", e));
+        weights
+    }
+
+    // Baked weights with the file given by the `weights` setting over them
+    pub fn from_settings() -> EvalWeights {
+        let mut weights = EvalWeights::baked();
+        if let Some(path) = setting("weights") {
+            let text = fs::read_to_string(&path).unwrap_or_else(|e| panic!("Failed to read {}: {}", path, e));
+            weights.apply(&text).unwrap_or_else(|e| panic!("Invalid weights in {}: {}", path, e));
+        }
+        weights
+    }
+
+    // Sets the weights listed in `text`, in the weights file format
+    pub fn apply(&mut self, text: &str) -> Result<(), String> {
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            let (name, value) = line.split_once(char::is_whitespace).ok_or_else(|| format!("No value in \"{}\"", line))?;
+            let value: f64 = value.trim().parse().map_err(|_| format!("Invalid value for {}: {}", name, value.trim()))?;
+            if !self.set(name, value) {
+                return Err(format!("Unknown weight {}", name));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<f64> {
+        let value = match name {
+            "grow_seed_bonus" => self.grow_seed_bonus as f64,
+            "grow_late_day" => self.grow_late_day as f64,
+            "grow_late_base" => self.grow_late_base as f64,
+            "complete_late_day" => self.complete_late_day as f64,
+            "complete_late_bonus" => self.complete_late_bonus as f64,
+            "harvest_bonus_after_day" => self.harvest_bonus_after_day as f64,
+            "seed_empty_neighbor_penalty" => self.seed_empty_neighbor_penalty as f64,
+            "seed_center_divisor" => self.seed_center_divisor as f64,
+            "seed_richness" => self.seed_richness as f64,
+            "sun_as_points_until_day" => self.sun_as_points_until_day as f64,
+            "tree_maturity_0" => self.tree_maturity[0],
+            "tree_maturity_1" => self.tree_maturity[1],
+            "tree_maturity_2" => self.tree_maturity[2],
+            "tree_maturity_3" => self.tree_maturity[3],
+            "sun_income_share" => self.sun_income_share,
+            "seed_max_trees" => self.seed_max_trees as f64,
+            "seed_until_day" => self.seed_until_day as f64,
+            "greedy_complete_from_day" => self.greedy_complete_from_day as f64,
+            "horizon" => self.horizon as f64,
+            "temperature" => self.temperature,
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    // Returns false for an unknown name
+    pub fn set(&mut self, name: &str, value: f64) -> bool {
+        let rounded = value.round() as i32;
+        match name {
+            "grow_seed_bonus" => self.grow_seed_bonus = rounded,
+            "grow_late_day" => self.grow_late_day = rounded,
+            "grow_late_base" => self.grow_late_base = rounded,
+            "complete_late_day" => self.complete_late_day = rounded,
+            "complete_late_bonus" => self.complete_late_bonus = rounded,
+            "harvest_bonus_after_day" => self.harvest_bonus_after_day = rounded,
+            "seed_empty_neighbor_penalty" => self.seed_empty_neighbor_penalty = rounded,
+            // a divisor of zero would panic
+            "seed_center_divisor" => self.seed_center_divisor = rounded.max(1),
+            "seed_richness" => self.seed_richness = rounded,
+            "sun_as_points_until_day" => self.sun_as_points_until_day = rounded,
+            "tree_maturity_0" => self.tree_maturity[0] = value,
+            "tree_maturity_1" => self.tree_maturity[1] = value,
+            "tree_maturity_2" => self.tree_maturity[2] = value,
+            "tree_maturity_3" => self.tree_maturity[3] = value,
+            "sun_income_share" => self.sun_income_share = value,
+            "seed_max_trees" => self.seed_max_trees = rounded,
+            "seed_until_day" => self.seed_until_day = rounded,
+            "greedy_complete_from_day" => self.greedy_complete_from_day = rounded,
+            "horizon" => self.horizon = rounded,
+            "temperature" => self.temperature = value,
+            _ => return false,
+        }
+        true
+    }
+}
+
+// In the weights file format, every weight listed
+impl fmt::Display for EvalWeights {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for name in WEIGHT_NAMES {
+            writeln!(f, "{} {}", name, self.get(name).unwrap())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weights_file_round_trip() {
+        let mut weights = EvalWeights::default();
+        weights.apply("# playouts\nhorizon 6\ntemperature inf\n\ntree_maturity_2 0.7\n").unwrap();
+        assert_eq!(weights.horizon, 6);
+        assert_eq!(weights.temperature, f64::INFINITY);
+
+        let mut read = EvalWeights::default();
+        read.apply(&weights.to_string()).unwrap();
+        assert_eq!(read, weights);
+        assert_eq!(WEIGHT_NAMES.iter().filter(|name| weights.get(name).is_some()).count(), WEIGHT_NAMES.len());
+        assert!(weights.apply("horizon_days 6").is_err());
+    }
+}
@@ -0,0 +1,3 @@
+// Tuned weights baked into the bot, in the weights file format. Empty for the
+// defaults, see `cargo run --bin bundle -- --weights file` to bake a file.
+pub const WEIGHTS: &str = "";